@@ -0,0 +1,60 @@
+//! Splitting a total point budget between the G1 and G2 sets.
+//!
+//! The split is decided before generation starts instead of having the two generator threads draw
+//! from a shared atomic counter and stop once it runs out. With a counter, the counts would depend
+//! on the relative speed of the threads, so the same arguments could produce different sets, and
+//! an interrupted run couldn't be resumed: the resumed run couldn't tell how many points each
+//! thread was meant to stop at. Preallocating gives each curve a fixed count recorded in its
+//! layout, which is deterministic, resumable, and never exceeds the budget either.
+
+/// How `allocate` splits a budget that's smaller than the requested counts.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum Policy {
+    /// Scale both counts down by the same factor, G2 getting the rounding leftover.
+    Proportional,
+    /// Give G1 as much of the budget as it requested, and G2 whatever is left.
+    G1First,
+}
+
+/// Returns the G1 and G2 counts to generate so that their sum doesn't exceed `total`. The requested
+/// counts are returned unchanged if they fit.
+pub fn allocate(policy: Policy, g1_count: u64, g2_count: u64, total: u64) -> (u64, u64) {
+    let requested = g1_count as u128 + g2_count as u128;
+    if requested <= total as u128 {
+        return (g1_count, g2_count);
+    }
+    let g1 = match policy {
+        Policy::Proportional => (total as u128 * g1_count as u128 / requested) as u64,
+        Policy::G1First => g1_count.min(total),
+    };
+    (g1, g2_count.min(total - g1))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn allocations_never_exceed_the_budget() {
+        for policy in [Policy::Proportional, Policy::G1First] {
+            for (g1, g2, total) in [(100, 100, 50), (100, 3, 50), (3, 100, 50), (7, 5, 11)] {
+                let (g1_count, g2_count) = allocate(policy, g1, g2, total);
+                assert!(
+                    g1_count + g2_count <= total,
+                    "{:?}",
+                    (policy, g1, g2, total)
+                );
+                assert!(g1_count <= g1 && g2_count <= g2);
+            }
+            assert_eq!(allocate(policy, 10, 5, 20), (10, 5));
+        }
+    }
+
+    #[test]
+    fn policies_split_the_budget_as_documented() {
+        assert_eq!(allocate(Policy::Proportional, 300, 100, 200), (150, 50));
+        assert_eq!(allocate(Policy::Proportional, 7, 5, 11), (6, 5));
+        assert_eq!(allocate(Policy::G1First, 300, 100, 200), (200, 0));
+        assert_eq!(allocate(Policy::G1First, 150, 100, 200), (150, 50));
+    }
+}
@@ -0,0 +1,211 @@
+//! Self-contained KZG test bundles.
+//!
+//! A bundle exercises a set end to end, for QA: commitments to random polynomials made with its G1
+//! powers, openings of them at random points, and the verifier key to check the openings with.
+//! For a commitment C to p, a point z, the value y = p(z), and the proof π, a commitment to the
+//! quotient (p(X) - y) / (X - z), an opening is valid if e(C - y·G1, G2) = e(π, tau·G2 - z·G2),
+//! i.e. if C - y·G1 + z·π is tau times π, which is what `Point::check_powers_against` checks.
+//!
+//! Bundles are JSON files, with points as hex-encoded compressed encodings and scalars as
+//! big-endian hex.
+
+use crate::curve::{Encoding, G1, G2, Point};
+use crate::layout::Layout;
+use crate::manifest::Manifest;
+use crate::reader;
+use crate::tau::{self, Endianness};
+use crate::verifier_key::VerifierKey;
+use anyhow::{Context, Result, anyhow};
+use blstrs::Scalar;
+use ff::Field;
+use group::Group;
+use rand_chacha::ChaCha20Rng;
+use rand_chacha::rand_core::SeedableRng;
+use serde::{Deserialize, Serialize};
+
+pub const VERSION: u32 = 1;
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Opening {
+    pub commitment: String,
+    pub point: String,
+    pub value: String,
+    pub proof: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Bundle {
+    pub version: u32,
+    /// Degree of the committed polynomials.
+    pub degree: usize,
+    /// Verifier key: the G2 generator and tau·G2, points 0 and 1 of the G2 set.
+    pub g2: String,
+    pub tau_g2: String,
+    pub openings: Vec<Opening>,
+}
+
+/// Checks that the `P` set described by `layout` holds at least `count` consecutive powers of tau
+/// times the standard generator, starting from tau^0.
+fn check_layout<P: Point>(layout: &Layout, count: u64) -> Result<()> {
+    if !layout.is_plain() {
+        return Err(anyhow!(
+            "the {} set doesn't hold consecutive powers of tau times the standard generator",
+            P::NAME
+        ));
+    }
+    if layout.count < count {
+        return Err(anyhow!(
+            "the {} set has {} points, {} are needed",
+            P::NAME,
+            layout.count,
+            count
+        ));
+    }
+    Ok(())
+}
+
+fn encode_point<P: Point>(point: &P) -> String {
+    hex::encode(point.to_bytes())
+}
+
+fn decode_point<P: Point>(hex: &str, name: &str) -> Result<P> {
+    let bytes = hex::decode(hex).map_err(|error| anyhow!("invalid {} hex: {}", name, error))?;
+    P::decode(Encoding::Compressed, &bytes).with_context(|| format!("parsing {}", name))
+}
+
+fn decode_scalar(hex: &str, name: &str) -> Result<Scalar> {
+    let scalar =
+        tau::parse_scalar_hex(hex, Endianness::Big).with_context(|| format!("parsing {}", name))?;
+    Ok(*scalar)
+}
+
+/// Evaluates the polynomial with coefficients `coefficients` (constant term first) at `z`.
+fn evaluate(coefficients: &[Scalar], z: &Scalar) -> Scalar {
+    coefficients
+        .iter()
+        .rev()
+        .fold(Scalar::ZERO, |value, coefficient| value * z + coefficient)
+}
+
+/// Returns the coefficients of (p(X) - p(z)) / (X - z), by synthetic division.
+fn quotient(coefficients: &[Scalar], z: &Scalar) -> Vec<Scalar> {
+    let mut quotient = vec![Scalar::ZERO; coefficients.len() - 1];
+    let mut carry = Scalar::ZERO;
+    for i in (1..coefficients.len()).rev() {
+        carry = coefficients[i] + carry * z;
+        quotient[i - 1] = carry;
+    }
+    quotient
+}
+
+impl Bundle {
+    /// Commits to `count` random polynomials of degree `degree` with the G1 powers of the set
+    /// described by `manifest`, and opens each of them at a random point.
+    pub fn make(manifest: &Manifest, count: usize, degree: usize) -> Result<Self> {
+        if degree == 0 {
+            return Err(anyhow!("the degree must be positive"));
+        }
+        let g1 = &manifest
+            .curves
+            .get(G1::NAME)
+            .ok_or_else(|| anyhow!("the manifest has no {} entry", G1::NAME))?
+            .layout;
+        let g2 = &manifest
+            .curves
+            .get(G2::NAME)
+            .ok_or_else(|| anyhow!("the manifest has no {} entry", G2::NAME))?
+            .layout;
+        check_layout::<G1>(g1, degree as u64 + 1)?;
+        check_layout::<G2>(g2, 2)?;
+        let bases: Vec<G1> = (0..=degree as u64)
+            .map(|index| reader::read_point::<G1>(g1, index))
+            .collect::<Result<_>>()?;
+        let tau_g2 = reader::read_point::<G2>(g2, 1)?;
+
+        let mut seed = [0u8; 32];
+        getrandom::fill(&mut seed)
+            .map_err(|error| anyhow!("no system randomness available: {}", error))?;
+        let mut rng = ChaCha20Rng::from_seed(seed);
+        let mut openings = Vec::with_capacity(count);
+        for _ in 0..count {
+            let coefficients: Vec<Scalar> =
+                (0..=degree).map(|_| Scalar::random(&mut rng)).collect();
+            let z = Scalar::random(&mut rng);
+            let value = evaluate(&coefficients, &z);
+            let quotient = quotient(&coefficients, &z);
+            openings.push(Opening {
+                commitment: encode_point(&G1::multi_exp(&bases, &coefficients)),
+                point: hex::encode(z.to_bytes_be()),
+                value: hex::encode(value.to_bytes_be()),
+                proof: encode_point(&G1::multi_exp(&bases[..degree], &quotient)),
+            });
+        }
+        Ok(Self {
+            version: VERSION,
+            degree,
+            g2: encode_point(&G2::generator()),
+            tau_g2: encode_point(&tau_g2),
+            openings,
+        })
+    }
+
+    pub fn load(path: &str) -> Result<Self> {
+        let json = std::fs::read_to_string(path).with_context(|| format!("reading {}", path))?;
+        let bundle: Self =
+            serde_json::from_str(json.as_str()).with_context(|| format!("parsing {}", path))?;
+        if bundle.version != VERSION {
+            return Err(anyhow!(
+                "{}: unsupported bundle version {}",
+                path,
+                bundle.version
+            ));
+        }
+        Ok(bundle)
+    }
+
+    pub fn save(&self, path: &str) -> Result<()> {
+        let mut json = serde_json::to_string_pretty(self)?;
+        json.push('\n');
+        std::fs::write(path, json).with_context(|| format!("writing {}", path))
+    }
+
+    /// Checks each opening against the verifier key of the bundle. Returns one message per opening
+    /// that doesn't verify.
+    pub fn verify(&self) -> Result<Vec<String>> {
+        if decode_point::<G2>(self.g2.as_str(), "the G2 generator")? != G2::generator() {
+            return Err(anyhow!(
+                "the bundle's verifier key isn't based on the standard G2 generator"
+            ));
+        }
+        let tau_g2 = decode_point::<G2>(self.tau_g2.as_str(), "tau·G2")?;
+        Ok(self.check_openings(&tau_g2))
+    }
+
+    /// Like `verify`, but checks the openings against a separately distributed verifier key
+    /// instead of the one in the bundle.
+    pub fn verify_with(&self, key: &VerifierKey) -> Vec<String> {
+        self.check_openings(&key.tau_g2)
+    }
+
+    fn check_openings(&self, tau_g2: &G2) -> Vec<String> {
+        let mut encoded = vec![];
+        tau_g2.encode(Encoding::Compressed, &mut encoded);
+        let mut problems = vec![];
+        for (index, opening) in self.openings.iter().enumerate() {
+            let check = || -> Result<bool> {
+                let commitment = decode_point::<G1>(opening.commitment.as_str(), "commitment")?;
+                let z = decode_scalar(opening.point.as_str(), "point")?;
+                let value = decode_scalar(opening.value.as_str(), "value")?;
+                let proof = decode_point::<G1>(opening.proof.as_str(), "proof")?;
+                let shifted = commitment - G1::generator() * value + proof * z;
+                G1::check_powers_against(&[proof, shifted], &encoded)
+            };
+            match check() {
+                Ok(true) => {}
+                Ok(false) => problems.push(format!("opening {} doesn't verify", index)),
+                Err(error) => problems.push(format!("opening {}: {:#}", index, error)),
+            }
+        }
+        problems
+    }
+}
@@ -0,0 +1,106 @@
+//! Verification of sets against the output of a multi-party ceremony.
+//!
+//! Ceremonies publish tau·G1 and tau·G2 for their final tau, which nobody knows. A set holds the
+//! powers of that tau if, in each group, its points at index 0 and 1 are G and the published tau·G,
+//! and every point is tau times the previous one, which pairings with the published point of the
+//! other group check without tau.
+
+use crate::chunk;
+use crate::curve::Point;
+use crate::manifest::CurveManifest;
+use crate::validate::Problem;
+
+/// Checks the `P` set described by `curve` against the published `tau` times the generator of `P`
+/// and `tau_other`, the compressed encoding of the published tau times the generator of the other
+/// group. Reads the whole set. Returns the problems found.
+pub fn verify<P: Point>(curve: &CurveManifest, tau: &P, tau_other: &[u8]) -> Vec<Problem> {
+    let layout = &curve.layout;
+    if !layout.is_plain() {
+        return vec![Problem {
+            chunk: None,
+            message: format!(
+                "the {} set doesn't hold consecutive powers of tau times the standard generator",
+                P::NAME
+            ),
+        }];
+    }
+    if layout.count < 2 {
+        return vec![Problem {
+            chunk: None,
+            message: format!(
+                "the {} set has {} points, at least 2 are needed to compare it with the ceremony",
+                P::NAME,
+                layout.count
+            ),
+        }];
+    }
+    check_chain(
+        curve,
+        tau_other,
+        &[
+            (0, P::generator(), "the ceremony's G"),
+            (1, *tau, "the ceremony's tau·G"),
+        ],
+    )
+}
+
+/// Checks that the `P` set described by `curve` holds consecutive powers of the tau of
+/// `tau_other`, the compressed encoding of tau times the generator of the other group, chunk
+/// boundaries included, and that the points at the indices in `known` are the given ones, described
+/// by their names. Reads the whole set. Returns the problems found.
+pub fn check_chain<P: Point>(
+    curve: &CurveManifest,
+    tau_other: &[u8],
+    known: &[(u64, P, &str)],
+) -> Vec<Problem> {
+    let layout = &curve.layout;
+    let mut problems = vec![];
+    let mut previous: Option<P> = None;
+    for index in 0..layout.chunk_count() {
+        let points = match layout
+            .chunk_path::<P>(index)
+            .and_then(|path| chunk::read::<P>(path.as_str()))
+        {
+            Ok(points) => points,
+            Err(error) => {
+                problems.push(Problem {
+                    chunk: Some(index),
+                    message: format!("chunk {}: {:#}", index, error),
+                });
+                previous = None;
+                continue;
+            }
+        };
+        let start = layout.chunk_start(index);
+        for &(global_index, expected, name) in known {
+            if let Some(point) = global_index
+                .checked_sub(start)
+                .and_then(|offset| points.get(offset as usize))
+                && *point != expected
+            {
+                problems.push(Problem {
+                    chunk: Some(index),
+                    message: format!("{} point {} isn't {}", P::NAME, global_index, name),
+                });
+            }
+        }
+        // Prepending the last point of the previous chunk also checks the boundary between them.
+        let chain: Vec<P> = previous.into_iter().chain(points.iter().copied()).collect();
+        match P::check_powers_against(&chain, tau_other) {
+            Ok(true) => {}
+            Ok(false) => problems.push(Problem {
+                chunk: Some(index),
+                message: format!("chunk {} doesn't hold consecutive powers of tau", index),
+            }),
+            Err(error) => {
+                problems.push(Problem {
+                    chunk: None,
+                    message: format!("{:#}", error),
+                });
+                break;
+            }
+        }
+        previous = points.last().copied();
+    }
+    problems
+}
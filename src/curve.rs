@@ -0,0 +1,477 @@
+use crate::tau;
+use anyhow::{Result, anyhow};
+use blst::{
+    blst_bendian_from_fp, blst_fp, blst_fp_from_bendian, blst_fp2, blst_p1, blst_p1_affine,
+    blst_p2, blst_p2_affine,
+};
+use blstrs::{G1Affine, G1Projective, G2Affine, G2Projective, Scalar, pairing};
+use ff::Field;
+use group::prime::{PrimeCurve, PrimeCurveAffine};
+use group::{Curve, Group, UncompressedEncoding};
+use rand_chacha::ChaCha20Rng;
+use rand_chacha::rand_core::SeedableRng;
+use serde::{Deserialize, Serialize};
+
+#[cfg(feature = "toy")]
+pub use crate::toy::{ToyG1 as G1, ToyG2 as G2};
+/// The groups sets are generated in. The test-only `toy` feature swaps them for toy groups.
+#[cfg(not(feature = "toy"))]
+pub use blstrs::{G1Projective as G1, G2Projective as G2};
+
+/// How points are serialized in chunk files.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, clap::ValueEnum)]
+#[serde(rename_all = "kebab-case")]
+pub enum Encoding {
+    /// Standard compressed encoding (48 bytes per G1 point, 96 per G2 point).
+    Compressed,
+    /// Standard uncompressed affine encoding (96 bytes per G1 point, 192 per G2 point). Larger
+    /// but faster to load, as no square roots need to be computed.
+    Uncompressed,
+    /// Jacobian coordinates X, Y, Z as kept during generation, such that the affine point is
+    /// (X/Z², Y/Z³), written without normalization (144 bytes per G1 point, 288 per G2 point).
+    /// Consumers pay for the inversions instead, e.g. by normalizing in batches. Each coordinate is
+    /// a canonical big-endian base field element, with Fp2 elements written c1 first as in the
+    /// standard encodings.
+    Projective,
+    /// Affine coordinates x, y in blst's in-memory representation (96 bytes per G1 point, 192 per
+    /// G2 point), e.g. for upload to GPU buffers as is by MSM kernels built on it. Each base field
+    /// element is six little-endian 64-bit limbs, least significant first, of its Montgomery form
+    /// x·2^384 mod p, and Fp2 elements are c0 followed by c1. The identity is all zeros.
+    GpuLimbs,
+}
+
+impl Encoding {
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Compressed => "compressed",
+            Self::Uncompressed => "uncompressed",
+            Self::Projective => "projective",
+            Self::GpuLimbs => "gpu-limbs",
+        }
+    }
+}
+
+/// A BLS12-381 group we generate powers of tau in.
+pub trait Point: PrimeCurve<Scalar = Scalar, Affine: UncompressedEncoding> + Send + Sync {
+    /// Curve name as shown to users and recorded in file headers.
+    const NAME: &'static str;
+
+    /// Size in bytes of a point in the scratch format, i.e. its raw projective coordinates.
+    const SCRATCH_SIZE: usize;
+
+    /// Appends the X, Y, Z projective coordinates to `out` exactly as blst stores them: each base
+    /// field element is six little-endian 64-bit limbs in Montgomery form, and Fp2 elements are c0
+    /// followed by c1.
+    fn write_scratch(&self, out: &mut Vec<u8>);
+
+    /// Parses a point previously written by `write_scratch`.
+    fn read_scratch(bytes: &[u8]) -> Result<Self>;
+
+    /// Appends the coordinates in the `Encoding::Projective` format.
+    fn write_projective(&self, out: &mut Vec<u8>);
+
+    /// Parses a point written by `write_projective`, returning `None` if it's not a valid group
+    /// element.
+    fn read_projective(bytes: &[u8]) -> Option<Self>;
+
+    /// Appends the affine coordinates in the `Encoding::GpuLimbs` format.
+    fn write_limbs(&self, out: &mut Vec<u8>);
+
+    /// Parses a point written by `write_limbs`, returning `None` if it's not a valid group element.
+    fn read_limbs(bytes: &[u8]) -> Option<Self>;
+
+    /// Checks with pairings that each of `points` is tau times the previous one, using only the
+    /// generator of the other group and tau times it. The pairs are batched with random weights,
+    /// so this fails if there's no system randomness.
+    fn check_powers(points: &[Self], tau: &Scalar) -> Result<bool>;
+
+    /// Like `check_powers`, but given the compressed encoding of tau times the generator of the
+    /// other group instead of tau, e.g. from the transcript of a ceremony whose tau nobody knows.
+    fn check_powers_against(points: &[Self], tau_other: &[u8]) -> Result<bool>;
+
+    /// Multi-scalar multiplication: returns the sum of `scalars[i]·points[i]`. The default
+    /// implementation multiplies the points one by one.
+    fn multi_exp(points: &[Self], scalars: &[Scalar]) -> Self {
+        points
+            .iter()
+            .zip(scalars)
+            .map(|(point, scalar)| *point * scalar)
+            .sum()
+    }
+
+    /// Hashes `message` to a point with the standard random-oracle hash-to-curve suite, domain
+    /// separated by `dst`. Nobody knows the discrete logarithm of the result.
+    fn hash_to_curve(message: &[u8], dst: &[u8]) -> Self;
+
+    fn encoded_size(encoding: Encoding) -> usize {
+        match encoding {
+            Encoding::Compressed => Self::Repr::default().as_ref().len(),
+            // Both hold the two affine coordinates, only in different representations.
+            Encoding::Uncompressed | Encoding::GpuLimbs => {
+                <Self::Affine as UncompressedEncoding>::Uncompressed::default()
+                    .as_ref()
+                    .len()
+            }
+            Encoding::Projective => Self::SCRATCH_SIZE,
+        }
+    }
+
+    fn encode(&self, encoding: Encoding, out: &mut Vec<u8>) {
+        match encoding {
+            Encoding::Compressed => out.extend_from_slice(self.to_bytes().as_ref()),
+            Encoding::Uncompressed => {
+                out.extend_from_slice(self.to_affine().to_uncompressed().as_ref())
+            }
+            Encoding::Projective => self.write_projective(out),
+            Encoding::GpuLimbs => self.write_limbs(out),
+        }
+    }
+
+    /// Parses a point written by `encode`, checking that it's a valid group element.
+    fn decode(encoding: Encoding, bytes: &[u8]) -> Result<Self> {
+        if bytes.len() != Self::encoded_size(encoding) {
+            return Err(anyhow!(
+                "{} {} points are {} bytes long, got {}",
+                encoding.name(),
+                Self::NAME,
+                Self::encoded_size(encoding),
+                bytes.len()
+            ));
+        }
+        let point = match encoding {
+            Encoding::Compressed => {
+                let mut repr = Self::Repr::default();
+                repr.as_mut().copy_from_slice(bytes);
+                Self::from_bytes(&repr).into_option()
+            }
+            Encoding::Uncompressed => {
+                let mut repr = <Self::Affine as UncompressedEncoding>::Uncompressed::default();
+                repr.as_mut().copy_from_slice(bytes);
+                Self::Affine::from_uncompressed(&repr)
+                    .into_option()
+                    .map(|point| point.to_curve())
+            }
+            Encoding::Projective => Self::read_projective(bytes),
+            Encoding::GpuLimbs => Self::read_limbs(bytes),
+        };
+        point.ok_or_else(|| anyhow!("invalid {} point", Self::NAME))
+    }
+}
+
+/// Returns the random linear combinations sum(r_i·P_i) and sum(r_i·P_(i+1)) of the consecutive
+/// pairs of `points`, or `None` if there are no pairs. The second one is tau times the first one if
+/// and only if, with overwhelming probability, every point is tau times the previous one. Fails if
+/// there's no system randomness for the weights.
+fn random_combinations<P: Point>(points: &[P]) -> Result<Option<(P, P)>> {
+    if points.len() < 2 {
+        return Ok(None);
+    }
+    let mut rng = ChaCha20Rng::from_seed(tau::random_seed()?);
+    let weights: Vec<Scalar> = (1..points.len())
+        .map(|_| Scalar::random(&mut rng))
+        .collect();
+    let count = weights.len();
+    Ok(Some((
+        P::multi_exp(&points[..count], &weights),
+        P::multi_exp(&points[1..], &weights),
+    )))
+}
+
+fn write_fp(value: &blst_fp, out: &mut Vec<u8>) {
+    for limb in value.l {
+        out.extend_from_slice(&limb.to_le_bytes());
+    }
+}
+
+fn read_fp(bytes: &[u8]) -> blst_fp {
+    let mut value = blst_fp::default();
+    for (limb, bytes) in value.l.iter_mut().zip(bytes.chunks_exact(8)) {
+        *limb = u64::from_le_bytes(bytes.try_into().unwrap());
+    }
+    value
+}
+
+/// Like `read_fp`, but rejects limbs that aren't the Montgomery form of a base field element, i.e.
+/// that aren't less than p.
+fn read_reduced_fp(bytes: &[u8]) -> Option<blst_fp> {
+    let value = read_fp(bytes);
+    let mut canonical = vec![];
+    write_canonical_fp(&value, &mut canonical);
+    (read_canonical_fp(&canonical)?.l == value.l).then_some(value)
+}
+
+fn write_canonical_fp(value: &blst_fp, out: &mut Vec<u8>) {
+    let mut bytes = [0u8; 48];
+    unsafe { blst_bendian_from_fp(bytes.as_mut_ptr(), value) };
+    out.extend_from_slice(&bytes);
+}
+
+/// Parses a big-endian base field element, rejecting non-canonical encodings.
+fn read_canonical_fp(bytes: &[u8]) -> Option<blst_fp> {
+    let mut value = blst_fp::default();
+    unsafe { blst_fp_from_bendian(&mut value, bytes.as_ptr()) };
+    let mut canonical = vec![];
+    write_canonical_fp(&value, &mut canonical);
+    (canonical == bytes).then_some(value)
+}
+
+fn write_canonical_fp2(value: &blst_fp2, out: &mut Vec<u8>) {
+    write_canonical_fp(&value.fp[1], out);
+    write_canonical_fp(&value.fp[0], out);
+}
+
+fn read_canonical_fp2(bytes: &[u8]) -> Option<blst_fp2> {
+    Some(blst_fp2 {
+        fp: [
+            read_canonical_fp(&bytes[48..96])?,
+            read_canonical_fp(&bytes[0..48])?,
+        ],
+    })
+}
+
+fn write_fp2(value: &blst_fp2, out: &mut Vec<u8>) {
+    write_fp(&value.fp[0], out);
+    write_fp(&value.fp[1], out);
+}
+
+fn read_fp2(bytes: &[u8]) -> blst_fp2 {
+    blst_fp2 {
+        fp: [read_fp(&bytes[0..48]), read_fp(&bytes[48..96])],
+    }
+}
+
+fn read_reduced_fp2(bytes: &[u8]) -> Option<blst_fp2> {
+    Some(blst_fp2 {
+        fp: [
+            read_reduced_fp(&bytes[0..48])?,
+            read_reduced_fp(&bytes[48..96])?,
+        ],
+    })
+}
+
+impl Point for G1Projective {
+    const NAME: &'static str = "G1";
+    const SCRATCH_SIZE: usize = 48 * 3;
+
+    fn write_scratch(&self, out: &mut Vec<u8>) {
+        let raw: &blst_p1 = self.as_ref();
+        write_fp(&raw.x, out);
+        write_fp(&raw.y, out);
+        write_fp(&raw.z, out);
+    }
+
+    fn read_scratch(bytes: &[u8]) -> Result<Self> {
+        let mut point = G1Projective::identity();
+        *point.as_mut() = blst_p1 {
+            x: read_fp(&bytes[0..48]),
+            y: read_fp(&bytes[48..96]),
+            z: read_fp(&bytes[96..144]),
+        };
+        if !bool::from(point.is_on_curve()) {
+            return Err(anyhow!("scratch point is not on the G1 curve"));
+        }
+        Ok(point)
+    }
+
+    fn write_projective(&self, out: &mut Vec<u8>) {
+        let raw: &blst_p1 = self.as_ref();
+        write_canonical_fp(&raw.x, out);
+        write_canonical_fp(&raw.y, out);
+        write_canonical_fp(&raw.z, out);
+    }
+
+    fn read_projective(bytes: &[u8]) -> Option<Self> {
+        let mut point = G1Projective::identity();
+        *point.as_mut() = blst_p1 {
+            x: read_canonical_fp(&bytes[0..48])?,
+            y: read_canonical_fp(&bytes[48..96])?,
+            z: read_canonical_fp(&bytes[96..144])?,
+        };
+        let valid = point.is_on_curve() & point.to_affine().is_torsion_free();
+        bool::from(valid).then_some(point)
+    }
+
+    fn write_limbs(&self, out: &mut Vec<u8>) {
+        let affine = self.to_affine();
+        let raw: &blst_p1_affine = affine.as_ref();
+        write_fp(&raw.x, out);
+        write_fp(&raw.y, out);
+    }
+
+    fn read_limbs(bytes: &[u8]) -> Option<Self> {
+        let mut point = G1Affine::identity();
+        *point.as_mut() = blst_p1_affine {
+            x: read_reduced_fp(&bytes[0..48])?,
+            y: read_reduced_fp(&bytes[48..96])?,
+        };
+        let valid = point.is_on_curve() & point.is_torsion_free();
+        bool::from(valid).then(|| point.to_curve())
+    }
+
+    fn check_powers(points: &[Self], tau: &Scalar) -> Result<bool> {
+        let Some((previous, next)) = random_combinations(points)? else {
+            return Ok(true);
+        };
+        let tau_g2 = G2Projective::generator() * tau;
+        Ok(pairing(&next.to_affine(), &G2Affine::generator())
+            == pairing(&previous.to_affine(), &tau_g2.to_affine()))
+    }
+
+    fn check_powers_against(points: &[Self], tau_other: &[u8]) -> Result<bool> {
+        let tau_g2 = G2Projective::decode(Encoding::Compressed, tau_other)?;
+        let Some((previous, next)) = random_combinations(points)? else {
+            return Ok(true);
+        };
+        Ok(pairing(&next.to_affine(), &G2Affine::generator())
+            == pairing(&previous.to_affine(), &tau_g2.to_affine()))
+    }
+
+    fn multi_exp(points: &[Self], scalars: &[Scalar]) -> Self {
+        G1Projective::multi_exp(points, scalars)
+    }
+
+    fn hash_to_curve(message: &[u8], dst: &[u8]) -> Self {
+        G1Projective::hash_to_curve(message, dst, &[])
+    }
+}
+
+impl Point for G2Projective {
+    const NAME: &'static str = "G2";
+    const SCRATCH_SIZE: usize = 96 * 3;
+
+    fn write_scratch(&self, out: &mut Vec<u8>) {
+        let raw: &blst_p2 = self.as_ref();
+        write_fp2(&raw.x, out);
+        write_fp2(&raw.y, out);
+        write_fp2(&raw.z, out);
+    }
+
+    fn read_scratch(bytes: &[u8]) -> Result<Self> {
+        let mut point = G2Projective::identity();
+        *point.as_mut() = blst_p2 {
+            x: read_fp2(&bytes[0..96]),
+            y: read_fp2(&bytes[96..192]),
+            z: read_fp2(&bytes[192..288]),
+        };
+        if !bool::from(point.is_on_curve()) {
+            return Err(anyhow!("scratch point is not on the G2 curve"));
+        }
+        Ok(point)
+    }
+
+    fn write_projective(&self, out: &mut Vec<u8>) {
+        let raw: &blst_p2 = self.as_ref();
+        write_canonical_fp2(&raw.x, out);
+        write_canonical_fp2(&raw.y, out);
+        write_canonical_fp2(&raw.z, out);
+    }
+
+    fn read_projective(bytes: &[u8]) -> Option<Self> {
+        let mut point = G2Projective::identity();
+        *point.as_mut() = blst_p2 {
+            x: read_canonical_fp2(&bytes[0..96])?,
+            y: read_canonical_fp2(&bytes[96..192])?,
+            z: read_canonical_fp2(&bytes[192..288])?,
+        };
+        let valid = point.is_on_curve() & point.to_affine().is_torsion_free();
+        bool::from(valid).then_some(point)
+    }
+
+    fn write_limbs(&self, out: &mut Vec<u8>) {
+        let affine = self.to_affine();
+        let raw: &blst_p2_affine = affine.as_ref();
+        write_fp2(&raw.x, out);
+        write_fp2(&raw.y, out);
+    }
+
+    fn read_limbs(bytes: &[u8]) -> Option<Self> {
+        let mut point = G2Affine::identity();
+        *point.as_mut() = blst_p2_affine {
+            x: read_reduced_fp2(&bytes[0..96])?,
+            y: read_reduced_fp2(&bytes[96..192])?,
+        };
+        let valid = point.is_on_curve() & point.is_torsion_free();
+        bool::from(valid).then(|| point.to_curve())
+    }
+
+    fn check_powers(points: &[Self], tau: &Scalar) -> Result<bool> {
+        let Some((previous, next)) = random_combinations(points)? else {
+            return Ok(true);
+        };
+        let tau_g1 = G1Projective::generator() * tau;
+        Ok(pairing(&G1Affine::generator(), &next.to_affine())
+            == pairing(&tau_g1.to_affine(), &previous.to_affine()))
+    }
+
+    fn check_powers_against(points: &[Self], tau_other: &[u8]) -> Result<bool> {
+        let tau_g1 = G1Projective::decode(Encoding::Compressed, tau_other)?;
+        let Some((previous, next)) = random_combinations(points)? else {
+            return Ok(true);
+        };
+        Ok(pairing(&G1Affine::generator(), &next.to_affine())
+            == pairing(&tau_g1.to_affine(), &previous.to_affine()))
+    }
+
+    fn multi_exp(points: &[Self], scalars: &[Scalar]) -> Self {
+        G2Projective::multi_exp(points, scalars)
+    }
+
+    fn hash_to_curve(message: &[u8], dst: &[u8]) -> Self {
+        G2Projective::hash_to_curve(message, dst, &[])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn round_trip<P: Point>() {
+        let point = P::generator() * Scalar::from(7u64) + P::generator();
+        let mut bytes = vec![];
+        point.encode(Encoding::Projective, &mut bytes);
+        assert_eq!(bytes.len(), P::encoded_size(Encoding::Projective));
+        assert_eq!(P::decode(Encoding::Projective, &bytes).unwrap(), point);
+        // The coordinates aren't normalized, they're stored as computed.
+        let mut affine = vec![];
+        P::decode(Encoding::Compressed, point.to_bytes().as_ref())
+            .unwrap()
+            .encode(Encoding::Projective, &mut affine);
+        assert_ne!(bytes, affine);
+        bytes[1] ^= 1;
+        assert!(P::decode(Encoding::Projective, &bytes).is_err());
+    }
+
+    #[test]
+    fn projective_points_round_trip() {
+        round_trip::<G1Projective>();
+        round_trip::<G2Projective>();
+    }
+
+    fn limbs_round_trip<P: Point>() {
+        let point = P::generator() * Scalar::from(7u64);
+        let mut bytes = vec![];
+        point.encode(Encoding::GpuLimbs, &mut bytes);
+        assert_eq!(bytes.len(), P::encoded_size(Encoding::GpuLimbs));
+        assert_eq!(P::decode(Encoding::GpuLimbs, &bytes).unwrap(), point);
+        // Montgomery limbs, not the big-endian canonical coordinates.
+        let mut uncompressed = vec![];
+        point.encode(Encoding::Uncompressed, &mut uncompressed);
+        assert_ne!(bytes, uncompressed);
+        bytes[0] ^= 1;
+        assert!(P::decode(Encoding::GpuLimbs, &bytes).is_err());
+
+        let mut identity = vec![];
+        P::identity().encode(Encoding::GpuLimbs, &mut identity);
+        assert!(identity.iter().all(|&byte| byte == 0));
+        assert_eq!(
+            P::decode(Encoding::GpuLimbs, &identity).unwrap(),
+            P::identity()
+        );
+    }
+
+    #[test]
+    fn gpu_limbs_round_trip() {
+        limbs_round_trip::<G1Projective>();
+        limbs_round_trip::<G2Projective>();
+    }
+}
@@ -0,0 +1,135 @@
+//! Strided subsets of a set.
+//!
+//! Downsampling a set with stride `s` and offset `r` keeps the points at indices r, r+s, r+2s, ...
+//! and writes them as a new set, whose layout records the resulting offset and stride so that
+//! point i of the new set is known to be tau^(offset + i·stride)·G. The new set reuses the chunk
+//! length, format, and encoding of the source.
+
+use crate::chunk;
+use crate::curve::Point;
+use crate::layout::Layout;
+use crate::manifest::{ChunkEntry, CurveManifest};
+use anyhow::{Result, anyhow};
+
+/// Writes the points of the `P` set described by `source` at indices `offset`, `offset + stride`,
+/// ... as a new set with file pattern `pattern`, returning its manifest.
+pub fn downsample<P: Point>(
+    source: &CurveManifest,
+    stride: u64,
+    offset: u64,
+    pattern: &str,
+) -> Result<CurveManifest> {
+    let layout = &source.layout;
+    if stride == 0 {
+        return Err(anyhow!("the stride must be positive"));
+    }
+    if offset >= layout.count {
+        return Err(anyhow!(
+            "offset {} is out of range, the {} set has {} points",
+            offset,
+            P::NAME,
+            layout.count
+        ));
+    }
+    let count = (layout.count - offset - 1) / stride + 1;
+    // The lowest power is that of the last kept point in reverse sets.
+    let lowest = if layout.reverse {
+        offset + (count - 1) * stride
+    } else {
+        offset
+    };
+    let strided = Layout {
+        count,
+        pattern: pattern.to_string(),
+        offset: layout.power(lowest),
+        stride: layout.stride * stride,
+        ..layout.clone()
+    };
+    let mut chunks = vec![];
+    let mut source_chunk: Option<(u64, Vec<P>)> = None;
+    for index in 0..strided.chunk_count() {
+        let first = strided.chunk_start(index);
+        let mut points = Vec::with_capacity(strided.chunk_size(index) as usize);
+        for source_index in (first..first + strided.chunk_size(index)).map(|i| offset + i * stride)
+        {
+            let source_chunk_index = source_index / layout.chunk_length;
+            if source_chunk
+                .as_ref()
+                .is_none_or(|(loaded, _)| *loaded != source_chunk_index)
+            {
+                let path = layout.chunk_path::<P>(source_chunk_index)?;
+                source_chunk = Some((source_chunk_index, chunk::read::<P>(path.as_str())?));
+            }
+            let (_, source_points) = source_chunk.as_ref().unwrap();
+            let point = source_points
+                .get((source_index % layout.chunk_length) as usize)
+                .ok_or_else(|| {
+                    anyhow!(
+                        "chunk {} of the {} set is too short to contain index {}",
+                        source_chunk_index,
+                        P::NAME,
+                        source_index
+                    )
+                })?;
+            points.push(*point);
+        }
+        let path = strided.chunk_path::<P>(index)?;
+        let blake2b = chunk::write(
+            path.as_str(),
+            &points,
+            strided.format,
+            strided.format_version,
+            strided.block_size,
+            strided.encoding,
+            first,
+        )?;
+        chunks.push(ChunkEntry {
+            index,
+            path,
+            count: points.len() as u64,
+            blake2b,
+            provenance: None,
+        });
+    }
+    Ok(CurveManifest {
+        layout: strided,
+        chunks,
+        index_file: None,
+        hiding: None,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::curve::G1;
+    use blstrs::Scalar;
+
+    #[test]
+    fn downsampling_matches_generating_the_strided_set() {
+        let (source_dir, dir) = (tempfile::tempdir().unwrap(), tempfile::tempdir().unwrap());
+        let tau = Scalar::from(7u64);
+        let source = Layout::dense(source_dir.path(), 20, 4).write_set::<G1>(&tau);
+        let pattern = format!("{}/strided_{{}}.bin", source_dir.path().display());
+        let strided = downsample::<G1>(&source, 3, 2, pattern.as_str()).unwrap();
+        let expected = Layout {
+            count: 6,
+            offset: 2,
+            stride: 3,
+            ..Layout::dense(dir.path(), 20, 4)
+        }
+        .write_set::<G1>(&tau);
+        assert_eq!(
+            strided.layout,
+            Layout {
+                pattern,
+                ..expected.layout.clone()
+            }
+        );
+        assert_eq!(strided.chunks.len(), expected.chunks.len());
+        for (chunk, expected) in strided.chunks.iter().zip(&expected.chunks) {
+            assert_eq!(chunk.count, expected.count);
+            assert_eq!(chunk.blake2b, expected.blake2b);
+        }
+    }
+}
@@ -0,0 +1,139 @@
+//! Probabilistic equality check of two sets held by different parties.
+//!
+//! Both parties agree on a nonce (e.g. one runs `equality-challenge` and sends it to the other),
+//! derive the same pseudo-random sample of global indices from it, and hash the points at those
+//! indices. Matching responses mean the sets agree on every sampled point: if a fraction f of the
+//! points differ, `samples` samples miss all of them with probability (1 - f)^samples. Only
+//! global indices and point values are hashed, so sets with different chunk lengths or encodings
+//! can be compared.
+
+use crate::chunk;
+use crate::curve::{Encoding, G1, G2, Point};
+use crate::layout::Layout;
+use crate::manifest::Manifest;
+use anyhow::{Result, anyhow};
+use rand_chacha::ChaCha20Rng;
+use rand_chacha::rand_core::{RngCore, SeedableRng};
+
+/// Length of the nonces generated by `new_nonce`.
+pub const NONCE_LENGTH: usize = 32;
+
+/// Returns a fresh random nonce, or an error if there's no system randomness.
+pub fn new_nonce() -> Result<[u8; NONCE_LENGTH]> {
+    let mut nonce = [0u8; NONCE_LENGTH];
+    getrandom::fill(&mut nonce)
+        .map_err(|error| anyhow!("no system randomness available: {}", error))?;
+    Ok(nonce)
+}
+
+/// Derives `samples` global indices of a `curve` set of `count` points from `nonce`, sorted and
+/// possibly repeated.
+pub fn challenge_indices(nonce: &[u8], curve: &str, count: u64, samples: usize) -> Vec<u64> {
+    if count == 0 {
+        return vec![];
+    }
+    let seed = blake2b_simd::Params::new()
+        .hash_length(32)
+        .personal(b"GPEQUALITY")
+        .to_state()
+        .update(curve.as_bytes())
+        .update(nonce)
+        .finalize();
+    let mut rng = ChaCha20Rng::from_seed(seed.as_bytes().try_into().unwrap());
+    // The modulo bias is at most count / 2^64, i.e. 2^-32 for the largest sets.
+    let mut indices: Vec<u64> = (0..samples).map(|_| rng.next_u64() % count).collect();
+    indices.sort_unstable();
+    indices
+}
+
+/// Feeds the sampled points of the `P` set with the given layout into `state`.
+fn hash_samples<P: Point>(
+    state: &mut blake2b_simd::State,
+    layout: &Layout,
+    nonce: &[u8],
+    samples: usize,
+) -> Result<()> {
+    state.update(P::NAME.as_bytes());
+    state.update(&layout.count.to_le_bytes());
+    let indices = challenge_indices(nonce, P::NAME, layout.count, samples);
+    let mut loaded: Option<(u64, Vec<P>)> = None;
+    for index in indices {
+        let chunk_index = index / layout.chunk_length;
+        if loaded.as_ref().map(|(loaded, _)| *loaded) != Some(chunk_index) {
+            let path = layout.chunk_path::<P>(chunk_index)?;
+            loaded = Some((chunk_index, chunk::read::<P>(path.as_str())?));
+        }
+        let points = &loaded.as_ref().unwrap().1;
+        let point = points
+            .get((index % layout.chunk_length) as usize)
+            .ok_or_else(|| anyhow!("{} chunk {} is too short", P::NAME, chunk_index))?;
+        let mut bytes = vec![];
+        point.encode(Encoding::Compressed, &mut bytes);
+        state.update(&index.to_le_bytes());
+        state.update(&bytes);
+    }
+    Ok(())
+}
+
+/// Computes the response to the challenge `nonce` for the set described by `manifest`, sampling
+/// `samples` points of each curve. Returns it hex-encoded.
+pub fn response(manifest: &Manifest, nonce: &[u8], samples: usize) -> Result<String> {
+    let mut state = blake2b_simd::Params::new().hash_length(32).to_state();
+    for (name, curve) in &manifest.curves {
+        match name.as_str() {
+            G1::NAME => hash_samples::<G1>(&mut state, &curve.layout, nonce, samples)?,
+            G2::NAME => hash_samples::<G2>(&mut state, &curve.layout, nonce, samples)?,
+            _ => return Err(anyhow!("unknown curve {}", name)),
+        }
+    }
+    Ok(state.finalize().to_hex().to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use blstrs::Scalar;
+    use group::Group;
+
+    fn manifest(layout: &Layout, tau: &Scalar) -> Manifest {
+        let mut manifest = Manifest::default();
+        let curve = layout.write_set::<G1>(tau);
+        manifest.curves.insert(G1::NAME.to_string(), curve);
+        manifest
+    }
+
+    #[test]
+    fn equal_sets_give_equal_responses() {
+        let (a, b) = (tempfile::tempdir().unwrap(), tempfile::tempdir().unwrap());
+        let tau = Scalar::from(7u64);
+        let nonce = [1u8; NONCE_LENGTH];
+        // Chunk lengths don't matter.
+        let a = manifest(&Layout::dense(a.path(), 10, 4), &tau);
+        let b = manifest(&Layout::dense(b.path(), 10, 3), &tau);
+        assert_eq!(
+            response(&a, &nonce, 64).unwrap(),
+            response(&b, &nonce, 64).unwrap()
+        );
+    }
+
+    #[test]
+    fn a_single_different_point_changes_the_response() {
+        let (a, b) = (tempfile::tempdir().unwrap(), tempfile::tempdir().unwrap());
+        let tau = Scalar::from(7u64);
+        let nonce = [1u8; NONCE_LENGTH];
+        let layout = Layout::dense(b.path(), 10, 4);
+        let (a, b) = (
+            manifest(&Layout::dense(a.path(), 10, 4), &tau),
+            manifest(&layout, &tau),
+        );
+        let mut points = layout.compute::<G1>(&tau, 4, 4).unwrap();
+        points[2] = points[2].double();
+        let path = layout.chunk_path::<G1>(1).unwrap();
+        let (format, version, encoding) = (layout.format, layout.format_version, layout.encoding);
+        chunk::write(path.as_str(), &points, format, version, None, encoding, 4).unwrap();
+        assert_ne!(
+            response(&a, &nonce, 64).unwrap(),
+            response(&b, &nonce, 64).unwrap()
+        );
+    }
+}
@@ -0,0 +1,40 @@
+//! Estimates of the compute time of a run on this machine, from a micro-benchmark of the point
+//! multiplications it consists of (see `--deadline`).
+//!
+//! Only the computation is estimated: writing is assumed to keep up, which holds on local disks
+//! but not necessarily on slow network storage.
+
+use crate::curve::Point;
+use blstrs::Scalar;
+use ff::Field;
+use std::time::{Duration, Instant};
+
+/// Measures the number of `P` points this machine computes per second, by multiplying a point by a
+/// full-size scalar over and over for `duration`, like the generator does with tau.
+pub fn rate<P: Point>(duration: Duration) -> f64 {
+    let scalar = -Scalar::ONE.double();
+    let mut g = P::generator();
+    let mut count = 0u64;
+    let start = Instant::now();
+    while start.elapsed() < duration {
+        g *= scalar;
+        count += 1;
+    }
+    std::hint::black_box(g);
+    count as f64 / start.elapsed().as_secs_f64()
+}
+
+/// Estimates the time to compute `(count, rate)` G1 and G2 points, the curves being computed
+/// concurrently, with the total rate capped at `max_points_per_second` if given.
+pub fn compute_time(
+    g1: (u64, f64),
+    g2: (u64, f64),
+    max_points_per_second: Option<u64>,
+) -> Duration {
+    let seconds = |(count, rate): (u64, f64)| count as f64 / rate;
+    let mut total = seconds(g1).max(seconds(g2));
+    if let Some(cap) = max_points_per_second {
+        total = total.max((g1.0 + g2.0) as f64 / cap as f64);
+    }
+    Duration::try_from_secs_f64(total).unwrap_or(Duration::MAX)
+}
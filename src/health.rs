@@ -0,0 +1,126 @@
+//! Liveness endpoint for orchestrated runs.
+//!
+//! `GET /healthz` answers 200 while the progress counters keep advancing and 503 once they have
+//! been stuck for longer than the stall threshold, so that an orchestrator can restart a wedged run
+//! (e.g. with `--resume`). The counters are fed by the reporter thread once per second. Building
+//! indices and hiding keys and verifying the set don't advance them, so the threshold must cover
+//! those phases.
+
+use anyhow::{Context, Result};
+use std::io::{BufRead, BufReader, Write};
+use std::net::{SocketAddr, TcpListener, TcpStream};
+use std::sync::Arc;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+#[derive(Debug)]
+struct Observed {
+    counter: usize,
+    changed: Instant,
+}
+
+#[derive(Debug)]
+pub struct Health {
+    stall: Duration,
+    observed: Mutex<Observed>,
+}
+
+impl Health {
+    pub fn new(stall: Duration) -> Self {
+        Self {
+            stall,
+            observed: Mutex::new(Observed {
+                counter: 0,
+                changed: Instant::now(),
+            }),
+        }
+    }
+
+    /// Records the current value of a monotonic progress counter.
+    pub fn observe(&self, counter: usize) {
+        let mut observed = self.observed.lock().unwrap();
+        if counter != observed.counter {
+            observed.counter = counter;
+            observed.changed = Instant::now();
+        }
+    }
+
+    /// Returns how long the counter has been stuck for, if longer than the stall threshold.
+    pub fn stalled_for(&self) -> Option<Duration> {
+        let stuck = self.observed.lock().unwrap().changed.elapsed();
+        (stuck > self.stall).then_some(stuck)
+    }
+
+    /// Starts serving the endpoint on `address` in a background thread. Returns the bound address,
+    /// which tells the port if `address` has port 0.
+    pub fn serve(self: Arc<Self>, address: &str) -> Result<SocketAddr> {
+        let listener =
+            TcpListener::bind(address).with_context(|| format!("listening on {}", address))?;
+        let bound = listener
+            .local_addr()
+            .with_context(|| format!("listening on {}", address))?;
+        std::thread::spawn(move || {
+            for stream in listener.incoming().flatten() {
+                // Errors only concern the client, which may have hung up.
+                let _ = self.respond(stream);
+            }
+        });
+        Ok(bound)
+    }
+
+    fn respond(&self, mut stream: TcpStream) -> std::io::Result<()> {
+        stream.set_read_timeout(Some(Duration::from_secs(5)))?;
+        let mut request = String::new();
+        BufReader::new(&stream).read_line(&mut request)?;
+        let (status, body) = match request.split_whitespace().nth(1) {
+            Some("/healthz") => match self.stalled_for() {
+                None => ("200 OK", "ok\n".to_string()),
+                Some(stuck) => (
+                    "503 Service Unavailable",
+                    format!("no progress for {} seconds\n", stuck.as_secs()),
+                ),
+            },
+            _ => ("404 Not Found", "not found\n".to_string()),
+        };
+        write!(
+            stream,
+            "HTTP/1.1 {}\r\nContent-Type: text/plain\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+            status,
+            body.len(),
+            body
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Read;
+
+    fn get(address: &str, path: &str) -> String {
+        let mut stream = TcpStream::connect(address).unwrap();
+        write!(stream, "GET {} HTTP/1.1\r\n\r\n", path).unwrap();
+        let mut response = String::new();
+        stream.read_to_string(&mut response).unwrap();
+        response
+    }
+
+    #[test]
+    fn health_turns_red_when_progress_stalls() {
+        let health = Arc::new(Health::new(Duration::from_secs(1)));
+        let address = health.clone().serve("127.0.0.1:0").unwrap().to_string();
+        for counter in 1..=5 {
+            health.observe(counter);
+            std::thread::sleep(Duration::from_millis(100));
+            assert!(get(&address, "/healthz").starts_with("HTTP/1.1 200 OK"));
+        }
+        // The same count again isn't progress.
+        health.observe(5);
+        std::thread::sleep(Duration::from_millis(1500));
+        let response = get(&address, "/healthz");
+        assert!(response.starts_with("HTTP/1.1 503"), "{}", response);
+        assert!(get(&address, "/metrics").starts_with("HTTP/1.1 404"));
+        health.observe(6);
+        assert!(get(&address, "/healthz").starts_with("HTTP/1.1 200 OK"));
+    }
+}
@@ -0,0 +1,183 @@
+//! Structured hiding commitment keys.
+//!
+//! A hiding key pairs every tau^i·G of a set with tau^i·H, where H is a second base with no known
+//! discrete logarithm relative to G, obtained by hashing to the curve (see `base`). Hiding KZG
+//! commitments add a multiple of the H powers as blinding.
+//!
+//! Hiding chunk files are a bincode-encoded `Header` followed by `count` pairs, each being the G
+//! component followed by the H component, both serialized with the encoding in the header.
+
+use crate::chunk;
+use crate::curve::{Encoding, Point};
+use crate::layout::Layout;
+use crate::manifest::{self, ChunkEntry};
+use crate::powers;
+use anyhow::{Context, Result, anyhow};
+use blstrs::Scalar;
+use serde::{Deserialize, Serialize};
+
+const MAGIC: [u8; 8] = *b"GPHIDING";
+
+/// Hash-to-curve domain separation tag of the hiding base.
+const DST: &[u8] = b"GENERATE_PARAMS_HIDING_BASE_";
+
+/// Hashed to the curve to get the hiding base.
+const BASE_MESSAGE: &[u8] = b"hiding base";
+
+#[derive(Debug, Serialize, Deserialize)]
+struct Header {
+    magic: [u8; 8],
+    curve: String,
+    encoding: Encoding,
+    first_index: u64,
+    count: u64,
+    /// Compressed hiding base, so that files can't be mixed up with ones using another base.
+    base: Vec<u8>,
+}
+
+/// Hiding key of a set, as recorded in its manifest.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct HidingManifest {
+    /// Hex-encoded compressed hiding base H.
+    pub base: String,
+    /// File pattern of the hiding chunks, with the same placeholders as the set pattern.
+    pub pattern: String,
+    /// Chunk entries sorted by index.
+    pub chunks: Vec<ChunkEntry>,
+}
+
+/// Returns the hiding base H of `P`.
+pub fn base<P: Point>() -> P {
+    P::hash_to_curve(BASE_MESSAGE, DST)
+}
+
+fn compressed<P: Point>(point: &P) -> Vec<u8> {
+    let mut bytes = vec![];
+    point.encode(Encoding::Compressed, &mut bytes);
+    bytes
+}
+
+/// Returns the hex-encoded compressed hiding base of `P`, as recorded in manifests.
+pub fn base_hex<P: Point>() -> String {
+    hex::encode(compressed(&base::<P>()))
+}
+
+/// Computes the H components matching `points`, the powers of tau times G from `first_index` on,
+/// and writes the pairs to `path`. Returns the hash of the file.
+pub fn write_chunk<P: Point>(
+    path: &str,
+    tau: &Scalar,
+    points: &[P],
+    encoding: Encoding,
+    first_index: u64,
+) -> Result<String> {
+    let hiding = powers::compute_from(base::<P>(), tau, first_index, points.len());
+    let header = Header {
+        magic: MAGIC,
+        curve: P::NAME.to_string(),
+        encoding,
+        first_index,
+        count: points.len() as u64,
+        base: compressed(&base::<P>()),
+    };
+    let mut data = bincode::serde::encode_to_vec(&header, bincode::config::standard())?;
+    data.reserve(2 * points.len() * P::encoded_size(encoding));
+    for (g, h) in points.iter().zip(&hiding) {
+        g.encode(encoding, &mut data);
+        h.encode(encoding, &mut data);
+    }
+    chunk::write_atomically(path, &data)?;
+    Ok(manifest::hash(&data))
+}
+
+/// Writes the hiding key of the `P` set with the given layout, reading its G components back from
+/// the chunk files, and naming the hiding chunks after `pattern`.
+pub fn build<P: Point>(tau: &Scalar, layout: &Layout, pattern: &str) -> Result<HidingManifest> {
+    let hiding_layout = Layout {
+        pattern: pattern.to_string(),
+        ..layout.clone()
+    };
+    let mut chunks = vec![];
+    for index in 0..layout.chunk_count() {
+        let points = chunk::read::<P>(layout.chunk_path::<P>(index)?.as_str())?;
+        let path = hiding_layout.chunk_path::<P>(index)?;
+        let first_index = layout.chunk_start(index);
+        let blake2b = write_chunk(path.as_str(), tau, &points, layout.encoding, first_index)?;
+        chunks.push(ChunkEntry {
+            index,
+            path,
+            count: points.len() as u64,
+            blake2b,
+            provenance: None,
+        });
+    }
+    Ok(HidingManifest {
+        base: base_hex::<P>(),
+        pattern: pattern.to_string(),
+        chunks,
+    })
+}
+
+/// Reads a hiding chunk file, returning the global index of its first pair and the pairs.
+pub fn read_chunk<P: Point>(path: &str) -> Result<(u64, Vec<(P, P)>)> {
+    let data = std::fs::read(path).with_context(|| format!("reading {}", path))?;
+    let (header, length): (Header, usize) =
+        bincode::serde::decode_from_slice(&data, bincode::config::standard())
+            .with_context(|| format!("decoding {}", path))?;
+    if header.magic != MAGIC {
+        return Err(anyhow!("{} is not a hiding chunk file", path));
+    }
+    if header.curve != P::NAME || header.base != compressed(&base::<P>()) {
+        return Err(anyhow!(
+            "{} doesn't hold {} points with the expected hiding base",
+            path,
+            P::NAME
+        ));
+    }
+    let size = P::encoded_size(header.encoding);
+    let pairs = &data[length..];
+    if pairs.len() as u64 != 2 * header.count * size as u64 {
+        return Err(anyhow!("{} has a truncated or oversized body", path));
+    }
+    let pairs = pairs
+        .chunks_exact(2 * size)
+        .map(|pair| {
+            Ok((
+                P::decode(header.encoding, &pair[..size])?,
+                P::decode(header.encoding, &pair[size..])?,
+            ))
+        })
+        .collect::<Result<_>>()
+        .with_context(|| format!("decoding {}", path))?;
+    Ok((header.first_index, pairs))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::curve::G1;
+    use group::Group;
+
+    #[test]
+    fn pairs_hold_the_powers_of_tau_times_both_bases() {
+        let dir = tempfile::tempdir().unwrap();
+        let tau = Scalar::from(7u64);
+        let curve = Layout::dense(dir.path(), 10, 4).write_set::<G1>(&tau);
+        let pattern = format!("{}/hiding_{{}}.bin", dir.path().display());
+        let hiding = build::<G1>(&tau, &curve.layout, pattern.as_str()).unwrap();
+        assert_eq!(hiding.base, base_hex::<G1>());
+        assert_ne!(base::<G1>(), G1::generator());
+        let mut index = 0;
+        for entry in &hiding.chunks {
+            let (first_index, pairs) = read_chunk::<G1>(entry.path.as_str()).unwrap();
+            assert_eq!(first_index, index);
+            for (g, h) in pairs {
+                let power = powers::power(&tau, index);
+                assert_eq!(g, G1::generator() * power);
+                assert_eq!(h, base::<G1>() * power);
+                index += 1;
+            }
+        }
+        assert_eq!(index, 10);
+    }
+}
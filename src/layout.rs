@@ -0,0 +1,352 @@
+use crate::chunk::{self, Format};
+use crate::curve::{Encoding, G1, G2, Point};
+use crate::pattern::{self, Fields};
+use crate::powers;
+use anyhow::{Result, anyhow};
+use blstrs::Scalar;
+use ff::Field;
+use serde::{Deserialize, Serialize};
+
+/// A chunk file of a set, as planned by `Layout::plan`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PlannedChunk {
+    pub path: String,
+    pub curve: String,
+    pub index: u64,
+    pub first_index: u64,
+    pub count: u64,
+    /// Size in bytes of the chunk file.
+    pub byte_size: u64,
+}
+
+fn version_one() -> u8 {
+    1
+}
+
+fn is_zero(value: &u64) -> bool {
+    *value == 0
+}
+
+fn one() -> u64 {
+    1
+}
+
+fn is_one(value: &u64) -> bool {
+    *value == 1
+}
+
+fn is_false(value: &bool) -> bool {
+    !*value
+}
+
+/// How the points of a curve's set are split into chunk files.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Layout {
+    pub count: u64,
+    pub chunk_length: u64,
+    pub pattern: String,
+    pub encoding: Encoding,
+    /// Container format of the chunk files, bincode for sets predating the option.
+    #[serde(default)]
+    pub format: Format,
+    /// Version of the chunk file format (see `chunk`), 1 for sets predating versioning.
+    #[serde(default = "version_one")]
+    pub format_version: u8,
+    /// Lowest power of tau of a strided set (see `downsample`), that of its first point unless
+    /// `reverse`, 0 for dense sets.
+    #[serde(default, skip_serializing_if = "is_zero")]
+    pub offset: u64,
+    /// Distance between the powers of tau of consecutive points, 1 for dense sets.
+    #[serde(default = "one", skip_serializing_if = "is_one")]
+    pub stride: u64,
+    /// Size the chunk files are zero-padded to a multiple of, if any (see `--block-size`).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub block_size: Option<u64>,
+    /// Hex-encoded compressed point the powers of tau multiply, if not the standard generator (see
+    /// `parse_generator`).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub generator: Option<String>,
+    /// Whether the points are in decreasing order of powers of tau, the first one having the
+    /// highest power (see `--reverse`).
+    #[serde(default, skip_serializing_if = "is_false")]
+    pub reverse: bool,
+}
+
+impl Layout {
+    pub fn chunk_count(&self) -> u64 {
+        self.count.div_ceil(self.chunk_length)
+    }
+
+    /// Power of tau the point at `index` is multiplied by, which is `index` itself in dense sets.
+    pub fn power(&self, index: u64) -> u64 {
+        let position = if self.reverse {
+            self.count - 1 - index
+        } else {
+            index
+        };
+        self.offset + position * self.stride
+    }
+
+    /// Returns an index among the `count` ones from `first` whose point depends on tau, i.e. whose
+    /// power isn't 0, preferring the last one, for checking a provided tau against the set. Only
+    /// tau^0·G is the same for every tau, which is the last point of reverse sets.
+    pub fn checkable_index(&self, first: u64, count: u64) -> Option<u64> {
+        (first..first + count)
+            .rev()
+            .find(|&index| self.power(index) != 0)
+    }
+
+    /// Returns the factor between the exponents of consecutive points: tau^stride, or its inverse
+    /// in reverse sets.
+    pub fn step(&self, tau: &Scalar) -> Scalar {
+        let step = powers::power(tau, self.stride);
+        if self.reverse {
+            // Zero has no inverse, but its powers are all zero past the first one anyway.
+            step.invert().unwrap_or(Scalar::ZERO)
+        } else {
+            step
+        }
+    }
+
+    /// Computes the `count` points of a `P` set with this layout starting at global index
+    /// `first_index`.
+    pub fn compute<P: Point>(&self, tau: &Scalar, first_index: u64, count: u64) -> Result<Vec<P>> {
+        let step = self.step(tau);
+        let mut points = Vec::with_capacity(count as usize);
+        let mut g = self.base::<P>()? * powers::power(tau, self.power(first_index));
+        for _ in 0..count {
+            points.push(g);
+            g *= step;
+        }
+        Ok(points)
+    }
+
+    /// Whether point i of the set is tau^i times the standard generator, as KZG provers and
+    /// verifiers expect.
+    pub fn is_plain(&self) -> bool {
+        self.offset == 0 && self.stride == 1 && !self.reverse && self.generator.is_none()
+    }
+
+    /// Returns the point G of a `P` set with this layout, whose point i is tau^power(i)·G.
+    pub fn base<P: Point>(&self) -> Result<P> {
+        match &self.generator {
+            Some(generator) => decode_generator::<P>(generator),
+            None => Ok(P::generator()),
+        }
+    }
+
+    /// Global index of the first point of chunk `index`.
+    pub fn chunk_start(&self, index: u64) -> u64 {
+        index * self.chunk_length
+    }
+
+    /// Number of points in chunk `index`, the last chunk possibly being shorter.
+    pub fn chunk_size(&self, index: u64) -> u64 {
+        self.chunk_length
+            .min(self.count.saturating_sub(self.chunk_start(index)))
+    }
+
+    /// Describes chunk `index` of a set of `P` points, whether it exists or not.
+    pub fn plan_chunk<P: Point>(&self, index: u64) -> Result<PlannedChunk> {
+        let first_index = self.chunk_start(index);
+        let count = self.chunk_size(index);
+        Ok(PlannedChunk {
+            path: self.chunk_path::<P>(index)?,
+            curve: P::NAME.to_string(),
+            index,
+            first_index,
+            count,
+            byte_size: chunk::padded_size(
+                chunk::file_size::<P>(
+                    self.format,
+                    self.format_version,
+                    self.encoding,
+                    first_index,
+                    count,
+                )?,
+                self.block_size,
+            ),
+        })
+    }
+
+    /// Lists all the chunk files of a set of `P` points with this layout.
+    pub fn plan<P: Point>(&self) -> Result<Vec<PlannedChunk>> {
+        (0..self.chunk_count())
+            .map(|index| self.plan_chunk::<P>(index))
+            .collect()
+    }
+
+    /// Renders the path of chunk `index` of a set of `P` points.
+    pub fn chunk_path<P: Point>(&self, index: u64) -> Result<String> {
+        pattern::render(
+            self.pattern.as_str(),
+            &Fields {
+                curve: P::NAME,
+                index,
+                start: self.chunk_start(index),
+                count: self.chunk_size(index),
+                encoding: self.encoding.name(),
+            },
+        )
+    }
+}
+
+/// Returns the largest chunk length of `P` points whose bincode chunk files fit in a block of
+/// `block_size` bytes, whatever their first index.
+pub fn block_chunk_length<P: Point>(
+    block_size: u64,
+    format_version: u8,
+    encoding: Encoding,
+) -> Result<u64> {
+    // The header is largest with the largest first index, and its count takes at most 8 more bytes
+    // than a count of 0.
+    let header = chunk::file_size::<P>(Format::Bincode, format_version, encoding, u64::MAX, 0)? + 8;
+    let chunk_length = block_size.saturating_sub(header) / P::encoded_size(encoding) as u64;
+    if chunk_length < 2 {
+        return Err(anyhow!(
+            "a block of {} bytes can't hold 2 {} points",
+            block_size,
+            P::NAME
+        ));
+    }
+    Ok(chunk_length)
+}
+
+fn decode_generator<P: Point>(hex: &str) -> Result<P> {
+    let bytes = hex::decode(hex.trim().trim_start_matches("0x"))
+        .map_err(|error| anyhow!("invalid {} generator hex: {}", P::NAME, error))?;
+    let point = P::decode(Encoding::Compressed, &bytes)
+        .map_err(|error| anyhow!("invalid {} generator: {:#}", P::NAME, error))?;
+    // Decoding checks that the point is on the curve and in the prime-order subgroup, so any point
+    // but the identity has order r.
+    if bool::from(point.is_identity()) {
+        return Err(anyhow!("the {} generator can't be the identity", P::NAME));
+    }
+    Ok(point)
+}
+
+/// Parses a generator given as `standard` or as a hex-encoded compressed point of `P`, returning
+/// the value to record in `Layout::generator`: `None` for the standard generator, however it's
+/// given, so that such sets are identical to default ones.
+pub fn parse_generator<P: Point>(text: &str) -> Result<Option<String>> {
+    if text == "standard" {
+        return Ok(None);
+    }
+    let point = decode_generator::<P>(text)?;
+    if point == P::generator() {
+        return Ok(None);
+    }
+    Ok(Some(hex::encode(point.to_bytes())))
+}
+
+/// Lists the chunk files of a G1 and a G2 set with the given layouts, G1 first.
+pub fn plan_layout(g1: &Layout, g2: &Layout) -> Result<Vec<PlannedChunk>> {
+    let mut chunks = g1.plan::<G1>()?;
+    chunks.extend(g2.plan::<G2>()?);
+    Ok(chunks)
+}
+
+#[cfg(test)]
+impl Layout {
+    /// Returns the layout of a dense set of `count` points in chunks of `chunk_length`, written to
+    /// `dir` with the default encoding and format.
+    pub(crate) fn dense(dir: &std::path::Path, count: u64, chunk_length: u64) -> Self {
+        Self {
+            count,
+            chunk_length,
+            pattern: format!("{}/{{curve}}_{{}}.bin", dir.display()),
+            encoding: Encoding::Compressed,
+            format: Format::default(),
+            format_version: chunk::FORMAT_VERSION,
+            offset: 0,
+            stride: 1,
+            block_size: None,
+            generator: None,
+            reverse: false,
+        }
+    }
+
+    /// Writes chunk `index` of the `P` set of `tau` as `chunk::write` would, but claiming to start
+    /// at `first_index`, returning its manifest entry.
+    pub(crate) fn write_chunk_at<P: Point>(
+        &self,
+        tau: &Scalar,
+        index: u64,
+        first_index: u64,
+    ) -> crate::manifest::ChunkEntry {
+        let count = self.chunk_size(index);
+        let path = self.chunk_path::<P>(index).unwrap();
+        let points = self
+            .compute::<P>(tau, self.chunk_start(index), count)
+            .unwrap();
+        let blake2b = chunk::write(
+            path.as_str(),
+            &points,
+            self.format,
+            self.format_version,
+            self.block_size,
+            self.encoding,
+            first_index,
+        )
+        .unwrap();
+        crate::manifest::ChunkEntry {
+            index,
+            path,
+            count,
+            blake2b,
+            provenance: None,
+        }
+    }
+
+    /// Writes all the chunks of the `P` set of `tau`, returning its manifest.
+    pub(crate) fn write_set<P: Point>(&self, tau: &Scalar) -> crate::manifest::CurveManifest {
+        crate::manifest::CurveManifest {
+            layout: self.clone(),
+            chunks: (0..self.chunk_count())
+                .map(|index| self.write_chunk_at::<P>(tau, index, self.chunk_start(index)))
+                .collect(),
+            index_file: None,
+            hiding: None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn planned_chunks_match_the_written_files() {
+        let dir = tempfile::tempdir().unwrap();
+        let tau = Scalar::from(7u64);
+        let g1 = Layout {
+            block_size: Some(512),
+            ..Layout::dense(dir.path(), 10, 4)
+        };
+        let g2 = Layout {
+            format: Format::Cbor,
+            encoding: Encoding::Uncompressed,
+            ..Layout::dense(dir.path(), 3, 2)
+        };
+        let written: Vec<_> = [g1.write_set::<G1>(&tau), g2.write_set::<G2>(&tau)]
+            .into_iter()
+            .flat_map(|curve| curve.chunks)
+            .collect();
+        let planned = plan_layout(&g1, &g2).unwrap();
+        assert_eq!(planned.len(), written.len());
+        for (planned, entry) in planned.iter().zip(&written) {
+            let header = chunk::read_file_header(entry.path.as_str()).unwrap();
+            assert_eq!(planned.path, entry.path);
+            assert_eq!(planned.curve, header.curve);
+            assert_eq!(planned.index, entry.index);
+            assert_eq!(planned.first_index, header.first_index);
+            assert_eq!(planned.count, header.count);
+            assert_eq!(
+                planned.byte_size,
+                std::fs::metadata(entry.path.as_str()).unwrap().len(),
+                "{}",
+                entry.path
+            );
+        }
+    }
+}
@@ -0,0 +1,37 @@
+//! Generation and handling of BLS12-381 powers-of-tau parameter sets.
+//!
+//! The `generate_params` binary is a thin command-line front end over this library.
+
+pub mod budget;
+pub mod bundle;
+pub mod ceremony;
+pub mod chunk;
+pub mod curve;
+pub mod downsample;
+pub mod equality;
+pub mod estimate;
+pub mod hiding;
+pub mod index;
+pub mod layout;
+pub mod manifest;
+pub mod msm;
+pub mod pattern;
+pub mod powers;
+pub mod pretable;
+pub mod reader;
+pub mod relayout;
+pub mod repair;
+pub mod resume;
+pub mod scratch;
+pub mod sealed;
+pub mod shifted;
+pub mod signature;
+pub mod stats;
+#[cfg(feature = "async")]
+pub mod stream;
+pub mod tau;
+#[cfg(feature = "toy")]
+pub mod toy;
+pub mod validate;
+pub mod verifier_key;
+pub mod view;
@@ -1,21 +1,318 @@
-use anyhow::{Result, anyhow};
-use blstrs::{G1Projective, G2Projective, Scalar};
-use clap::Parser;
-use dusk_bls12_381::BlsScalar as DuskScalar;
-use group::{Group, GroupEncoding};
-use primitive_types::{H384, H768};
-use std::fs::File;
-use std::io::Write;
+mod health;
+mod output;
+mod progress;
+mod throttle;
+
+use crate::health::Health;
+use crate::output::Output;
+use crate::progress::{CurveProgress, Etas};
+use crate::throttle::RateLimiter;
+use anyhow::{Context, Result, anyhow};
+use clap::{Parser, Subcommand, ValueEnum};
+use ed25519_dalek::SigningKey;
+use generate_params::bundle::Bundle;
+use generate_params::chunk::Format;
+use generate_params::curve::{Encoding, G1, G2, Point};
+use generate_params::layout::{self, Layout};
+use generate_params::manifest::{self, CeremonyRecord, ChunkEntry, CurveManifest, Manifest};
+use generate_params::relayout::Relayout;
+use generate_params::scratch::ScratchManifest;
+use generate_params::sealed::SealedTau;
+use generate_params::shifted::{self, ShiftedBundle};
+use generate_params::signature::Signatures;
+use generate_params::tau::{Endianness, SecretScalar};
+use generate_params::validate::Marker;
+use generate_params::verifier_key::VerifierKey;
+use generate_params::view::View;
+use generate_params::{
+    budget, ceremony, chunk, downsample, equality, estimate, hiding, index, msm, pattern, powers,
+    pretable, reader, relayout, repair, resume, scratch, signature, stats, tau, validate, view,
+};
+use group::GroupEncoding;
+use std::path::{Path, PathBuf};
 use std::pin::Pin;
-use std::sync::{Arc, Mutex, atomic::AtomicUsize, atomic::Ordering};
+use std::sync::{
+    Arc, Mutex,
+    atomic::{AtomicBool, Ordering},
+    mpsc,
+};
 use std::thread::JoinHandle;
 use std::time::{Duration, Instant};
+use zeroize::Zeroizing;
 
 const MAX_COUNT: usize = u32::MAX as usize + 1;
 
+/// Maximum number of computed chunks waiting for the writer thread of each curve.
+const WRITE_QUEUE_LENGTH: usize = 4;
+
+/// Number of points of each set checked by `--stats`.
+const STATS_SAMPLES: u64 = 4096;
+
+/// Largest G2 count generated on the main thread with `--no-g2-thread-when-small`.
+const SMALL_G2_COUNT: usize = 64;
+
 #[derive(Parser, Debug)]
-#[command(version, about, long_about = None)]
+#[command(version, about, long_about = None, args_conflicts_with_subcommands = true)]
 struct Args {
+    #[command(subcommand)]
+    command: Option<Command>,
+
+    #[command(flatten)]
+    generate: GenerateArgs,
+}
+
+#[derive(Subcommand, Debug)]
+enum Command {
+    /// Converts scratch files written with `--scratch` into regular chunk files, either listed
+    /// one by one or all those of a scratch manifest, which also writes the set manifest.
+    Finalize(FinalizeArgs),
+
+    /// Computes the G1 commitment to the vanishing polynomial X^n - 1 of a size-n subgroup, i.e.
+    /// tau^n·G1 - G1, from a generated set.
+    VanishingCommitment(VanishingCommitmentArgs),
+
+    /// Recomputes a single chunk of a set from its tau, rewrites it, and updates its manifest
+    /// entry.
+    RepairChunk(RepairChunkArgs),
+
+    /// Checks that the chunk headers of a set carry contiguous, gap-free global indices matching
+    /// their file names.
+    Validate(ValidateArgs),
+
+    /// Recomputes the chunks in a failure list written by `validate --failures`, then validates
+    /// the set again, hashes included.
+    Repair(RepairArgs),
+
+    /// Looks a point up in the index written with `--emit-index`, printing its global index if
+    /// it's in the set.
+    Lookup(LookupArgs),
+
+    /// Prints a fresh random nonce to challenge another party holding a supposedly equal set.
+    EqualityChallenge,
+
+    /// Answers an equality challenge by hashing a pseudo-random sample of points derived from its
+    /// nonce. Two sets that produce the same response agree on all the sampled points.
+    EqualityProof(EqualityProofArgs),
+
+    /// Writes the strided subset of a set at indices offset, offset + stride, ... as a new set,
+    /// e.g. for a coset setup, without regenerating it.
+    Downsample(DownsampleArgs),
+
+    /// Moves a set to a new file pattern or chunk length and updates its manifest, renaming the
+    /// chunk files if the chunk length is unchanged and re-chunking the set otherwise.
+    Relayout(RelayoutArgs),
+
+    /// Checks the signatures written with `--sign-key` against the manifest and a public key.
+    VerifySignature(VerifySignatureArgs),
+
+    /// Compares the manifests of two sets, e.g. supposedly identical copies, and lists the chunks
+    /// that differ in hash or point count, or that only one of them has.
+    ManifestDiff(ManifestDiffArgs),
+
+    /// Explains who may know the tau of a set, and what that implies for using it in production.
+    ExplainSecurity(ExplainSecurityArgs),
+
+    /// Writes a fixed-base MSM table of the G1 powers of a set, with the multiples of each power
+    /// needed to skip the doublings of windowed MSMs, in a layout provers can mmap.
+    PrecomputeMsm(PrecomputeMsmArgs),
+
+    /// Checks that a set holds the powers of the tau of a multi-party ceremony, given the tau·G1
+    /// and tau·G2 it published: points 0 and 1 must be G and the published points, and each point
+    /// tau times the previous one, as checked with pairings.
+    VerifyAgainstCeremony(VerifyAgainstCeremonyArgs),
+
+    /// Commits to random polynomials with the G1 powers of a set and opens them at random points,
+    /// writing the openings and the verifier key to a self-contained test bundle.
+    MakeTestBundle(MakeTestBundleArgs),
+
+    /// Checks the openings of a test bundle written by `make-test-bundle` against its verifier
+    /// key.
+    VerifyBundle(VerifyBundleArgs),
+
+    /// Tells from the manifest alone whether a set can be used to commit to polynomials of a given
+    /// degree with KZG, i.e. has enough consecutive G1 powers and the 2 G2 powers of the verifier
+    /// key, and by how many powers it falls short otherwise.
+    CheckDegree(CheckDegreeArgs),
+
+    /// Extracts the KZG verifier key of a set, i.e. the G1 generator, the G2 generator, and
+    /// tau·G2, to a small standalone file for distribution to verifiers.
+    TrimVerifierKey(TrimVerifierKeyArgs),
+
+    /// Generates a set and a copy shifted by a known power of the same tau in one go, as a
+    /// directory with a top-level manifest tying both to one tau handle, tau·G1 and tau·G2.
+    Bundle(BundleArgs),
+
+    /// Checks without tau that the sets of a directory written by `bundle` are the ones its
+    /// manifest records and share its tau.
+    VerifyBundleConsistency(VerifyBundleConsistencyArgs),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+enum Curve {
+    G1,
+    G2,
+}
+
+// Ways of providing tau instead of sampling it from system randomness. They are mutually
+// exclusive. This is a plain comment, as clap would show a doc comment of a flattened group as the
+// description of the binary.
+//
+// Command-line arguments are visible to other users through `ps` and tend to end up in shell
+// history, so `--tau` and `--seed-hex` are only suitable for test values. Real secrets should be
+// passed with `--tau-env`, `--seed-env`, `--seed-from-file`, or `--tau-sealed`.
+#[derive(clap::Args, Debug)]
+#[group(id = "tau_source", multiple = false)]
+struct TauSource {
+    /// Tau itself, as 32 bytes in hex, big-endian unless `--scalar-endianness little` is given.
+    #[arg(long)]
+    tau: Option<String>,
+
+    /// Like `--tau`, but reads tau from the given environment variable, e.g. as injected by a
+    /// secret manager.
+    #[arg(long, value_name = "VAR")]
+    tau_env: Option<String>,
+
+    /// Hex-encoded seed to deterministically derive tau from via ChaCha20. Seeds that are not
+    /// exactly 32 bytes long are hashed to 32 bytes with BLAKE2b.
+    #[arg(long)]
+    seed_hex: Option<String>,
+
+    /// Like `--seed-hex`, but reads the hex-encoded seed from the given environment variable.
+    #[arg(long, value_name = "VAR")]
+    seed_env: Option<String>,
+
+    /// Like `--seed-hex`, but reads the raw seed bytes from the given file.
+    #[arg(long)]
+    seed_from_file: Option<String>,
+
+    /// Reads tau from a file written by `--tau-encrypt`, decrypting it with the passphrase in the
+    /// variable named by `--passphrase-env`.
+    #[arg(long, value_name = "PATH", requires = "passphrase_env")]
+    tau_sealed: Option<String>,
+}
+
+#[derive(clap::Args, Debug)]
+struct TauArgs {
+    #[command(flatten)]
+    source: TauSource,
+
+    /// Byte order of `--tau` and `--tau-env`, for interoperability with tools using little-endian
+    /// scalars. Seeds are byte strings and have no byte order.
+    #[arg(long, value_enum, default_value_t = Endianness::Big)]
+    scalar_endianness: Endianness,
+
+    /// Environment variable holding the passphrase of `--tau-sealed` and `--tau-encrypt`.
+    #[arg(long, value_name = "VAR")]
+    passphrase_env: Option<String>,
+}
+
+/// Reads the environment variable `name`. The returned copy is zeroed on drop, but the process
+/// environment itself can't be cleared safely once threads may be running.
+fn read_secret_env(name: &str) -> Result<Zeroizing<String>> {
+    std::env::var(name)
+        .map(Zeroizing::new)
+        .map_err(|error| anyhow!("reading ${}: {}", name, error))
+}
+
+/// Validates `--g1-generator` and `--g2-generator`, normalizing encodings of the standard generator
+/// to `standard`.
+fn parse_generator<P: Point>(text: &str) -> std::result::Result<String, String> {
+    layout::parse_generator::<P>(text)
+        .map(|generator| generator.unwrap_or_else(|| "standard".to_string()))
+        .map_err(|error| format!("{:#}", error))
+}
+
+fn scalar_from_seed_hex(seed: &str) -> Result<SecretScalar> {
+    let seed = Zeroizing::new(
+        hex::decode(seed.trim().trim_start_matches("0x"))
+            .map_err(|error| anyhow!("invalid seed hex: {}", error))?,
+    );
+    tau::scalar_from_seed(seed.as_slice())
+}
+
+impl TauArgs {
+    /// Returns the provided tau, if any.
+    fn get(&self) -> Result<Option<SecretScalar>> {
+        let source = &self.source;
+        let endianness = self.scalar_endianness;
+        if let Some(tau) = &source.tau {
+            tau::parse_scalar_hex(tau, endianness).map(Some)
+        } else if let Some(name) = &source.tau_env {
+            tau::parse_scalar_hex(read_secret_env(name)?.trim(), endianness).map(Some)
+        } else if let Some(seed) = &source.seed_hex {
+            scalar_from_seed_hex(seed).map(Some)
+        } else if let Some(name) = &source.seed_env {
+            scalar_from_seed_hex(read_secret_env(name)?.as_str()).map(Some)
+        } else if let Some(path) = &source.seed_from_file {
+            let seed = Zeroizing::new(std::fs::read(path)?);
+            tau::scalar_from_seed(seed.as_slice()).map(Some)
+        } else if let Some(path) = &source.tau_sealed {
+            SealedTau::load(path)?
+                .open(self.passphrase()?.as_bytes())
+                .with_context(|| format!("opening {}", path))
+                .map(Some)
+        } else {
+            Ok(None)
+        }
+    }
+
+    /// Returns the passphrase of `--tau-sealed` and `--tau-encrypt`.
+    fn passphrase(&self) -> Result<Zeroizing<String>> {
+        let name = self
+            .passphrase_env
+            .as_deref()
+            .ok_or_else(|| anyhow!("--passphrase-env is required"))?;
+        read_secret_env(name)
+    }
+
+    fn get_required(&self) -> Result<SecretScalar> {
+        self.get()?.ok_or_else(|| {
+            anyhow!(
+                "the original tau is required (--tau, --tau-env, --seed-hex, --seed-env, \
+                 --seed-from-file, or --tau-sealed)"
+            )
+        })
+    }
+}
+
+/// G2 count presets relative to the G1 count, see `--g2-from-g1`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum G2Preset {
+    Minimal,
+    Symmetric,
+    Custom(usize),
+}
+
+impl std::str::FromStr for G2Preset {
+    type Err = anyhow::Error;
+
+    fn from_str(mode: &str) -> Result<Self> {
+        match mode {
+            "minimal" => Ok(Self::Minimal),
+            "symmetric" => Ok(Self::Symmetric),
+            _ => {
+                let count = mode
+                    .strip_prefix("custom:")
+                    .ok_or_else(|| anyhow!("expected minimal, symmetric, or custom:N"))?;
+                Ok(Self::Custom(count.parse()?))
+            }
+        }
+    }
+}
+
+impl G2Preset {
+    /// Returns the G2 count for the given G1 count, along with the reason for it.
+    fn resolve(self, g1_count: usize) -> (usize, &'static str) {
+        match self {
+            Self::Minimal => (2, "powers 0 and 1, enough for KZG verification"),
+            Self::Symmetric => (g1_count, "as many as G1"),
+            Self::Custom(count) => (count, "custom count"),
+        }
+    }
+}
+
+#[derive(clap::Args, Debug)]
+struct GenerateArgs {
     /// Number of BLS12-381 G1 points to generate, defaulting to `u32::MAX+1`.
     #[arg(long, default_value = "4294967296")]
     g1_count: usize,
@@ -24,14 +321,37 @@ struct Args {
     #[arg(long, default_value = "4294967296")]
     g2_count: usize,
 
-    /// G1 file pattern (for BLS12-381 G1).
+    /// Derive the G2 count from the G1 count instead: `minimal` for powers 0 and 1 only (vanilla
+    /// KZG), `symmetric` for as many as G1, or `custom:N` for N.
+    #[arg(long, value_name = "MODE", conflicts_with = "g2_count")]
+    g2_from_g1: Option<G2Preset>,
+
+    /// Maximum combined number of G1 and G2 points to generate. If the requested counts exceed it,
+    /// they're reduced according to `--budget-policy` before generation starts, rather than by
+    /// having both curves draw from a shared counter, so that the counts don't depend on thread
+    /// timing and an interrupted run can be resumed with the same arguments.
+    #[arg(long, value_name = "T")]
+    total_budget: Option<u64>,
+
+    /// How `--total-budget` is split between G1 and G2.
+    #[arg(long, value_enum, default_value_t = budget::Policy::Proportional)]
+    budget_policy: budget::Policy,
+
+    /// G1 file pattern (for BLS12-381 G1). Accepts the `{curve}`, `{index}` (or `{}`), `{start}`,
+    /// `{count}`, and `{encoding}` placeholders, numeric ones with an optional width, e.g.
+    /// `{start:07}`.
     #[arg(long, default_value = "g1_{}.bin")]
     g1_pattern: String,
 
-    /// G2 file pattern (for BLS12-381 G2).
+    /// G2 file pattern (for BLS12-381 G2), with the same placeholders as the G1 pattern.
     #[arg(long, default_value = "g2_{}.bin")]
     g2_pattern: String,
 
+    /// Extension of the chunk files, replacing that of the file patterns, e.g. `srs` to write
+    /// `g1_0.srs` instead of `g1_0.bin` with the default patterns. An empty extension removes it.
+    #[arg(long, value_name = "EXT")]
+    extension: Option<String>,
+
     /// Number of G1 points in each chunk.
     #[arg(long, default_value = "65536")]
     g1_chunk_length: usize,
@@ -39,26 +359,787 @@ struct Args {
     /// Number of G2 points in each chunk.
     #[arg(long, default_value = "65536")]
     g2_chunk_length: usize,
+
+    /// Size in bytes of the blocks of a block-based file system, e.g. 134217728 for HDFS: make
+    /// each chunk file exactly one block, with as many points as fit, zero-padded after the last
+    /// point. Overrides the chunk lengths and requires the bincode format.
+    #[arg(
+        long,
+        value_name = "BYTES",
+        conflicts_with_all = ["g1_chunk_length", "g2_chunk_length", "scratch"]
+    )]
+    block_size: Option<u64>,
+
+    /// Encoding of G1 points.
+    #[arg(long, value_enum, default_value_t = Encoding::Compressed)]
+    g1_encoding: Encoding,
+
+    /// Encoding of G2 points.
+    #[arg(long, value_enum, default_value_t = Encoding::Compressed)]
+    g2_encoding: Encoding,
+
+    /// Container format of chunk files. CBOR and MessagePack are self-describing and readable from
+    /// other languages, at the cost of a few bytes per point.
+    #[arg(long, value_enum, default_value_t = Format::Bincode)]
+    format: Format,
+
+    /// Version of the chunk file format, e.g. 1 to finish a set started in version 1. Resuming
+    /// picks the version of the existing chunks.
+    #[arg(
+        long,
+        value_name = "N",
+        default_value_t = chunk::FORMAT_VERSION,
+        value_parser = clap::value_parser!(u8).range(1..=chunk::FORMAT_VERSION as i64)
+    )]
+    format_version: u8,
+
+    /// Write raw projective coordinates to scratch files instead of compressed points, deferring
+    /// normalization and compression to the `finalize` command.
+    #[arg(long)]
+    scratch: bool,
+
+    /// Resume an interrupted run: reuse the leading chunks that are already complete on disk and
+    /// generate the rest, in the chunk format version of the existing chunks. Requires the tau of
+    /// the interrupted run.
+    #[arg(long, conflicts_with = "scratch")]
+    resume: bool,
+
+    /// After generation, also write a sorted index of each set's points for membership queries
+    /// with the `lookup` command, next to the manifest, e.g. `params.g1-index.bin`. Building it
+    /// takes about 56 bytes of RAM per G1 point and 104 per G2 point.
+    #[arg(long, conflicts_with = "scratch")]
+    emit_index: bool,
+
+    /// Record in the manifest when and where each chunk was generated: the time, host name, and
+    /// process ID. The chunk files, and thus the fingerprint, are unaffected. Chunks found complete
+    /// by `--resume` have no provenance.
+    #[arg(long, conflicts_with = "scratch")]
+    record_provenance: bool,
+
+    /// For preemptible machines: resume like `--resume`, and on SIGTERM or SIGINT stop computing,
+    /// write the chunks already computed, checkpoint the points of the current chunk to a partial
+    /// file, and exit. Only the time to write at most the queued chunks and one raw partial chunk
+    /// is needed after the signal; if the process is killed before that, the work lost is bounded
+    /// by the chunks not yet on disk, i.e. at most 5 times the chunk length per curve.
+    #[arg(long, conflicts_with = "scratch")]
+    spot_mode: bool,
+
+    /// Also write a hiding key for G1: pairs (tau^i·G1, tau^i·H) where H is a second base hashed to
+    /// the curve, for hiding KZG commitments.
+    #[arg(long, conflicts_with = "scratch")]
+    hiding: bool,
+
+    /// Generate only the even powers tau^0·G, tau^2·G, tau^4·G, ... of each curve, stepping by
+    /// tau^2, e.g. for quadratic arithmetic setups. The counts are numbers of points, and the
+    /// manifest records the set as strided, like `downsample` does.
+    #[arg(long, conflicts_with_all = ["odd_powers", "hiding"])]
+    even_powers: bool,
+
+    /// Like `--even-powers`, but for the odd powers tau^1·G, tau^3·G, tau^5·G, ...
+    #[arg(long, conflicts_with = "hiding")]
+    odd_powers: bool,
+
+    /// Generate the powers in decreasing order, from the highest one down to tau^0·G, stepping by
+    /// the inverse of tau, e.g. for consumers that process high-degree terms first. Chunk 0 holds
+    /// the highest powers, and the manifest records the order.
+    #[arg(long, conflicts_with = "hiding")]
+    reverse: bool,
+
+    /// Point G1 the G1 powers of tau multiply: `standard` for the standard generator, or a
+    /// hex-encoded compressed point, e.g. to match the generator of another implementation. It must
+    /// be in the prime-order subgroup and not the identity. Recorded in the manifest unless it's
+    /// the standard generator.
+    #[arg(
+        long,
+        value_name = "standard|HEX",
+        default_value = "standard",
+        value_parser = parse_generator::<G1>
+    )]
+    g1_generator: String,
+
+    /// Like `--g1-generator`, for G2.
+    #[arg(
+        long,
+        value_name = "standard|HEX",
+        default_value = "standard",
+        value_parser = parse_generator::<G2>
+    )]
+    g2_generator: String,
+
+    /// File pattern of the G1 hiding key, with the same placeholders as the G1 pattern.
+    #[arg(long, default_value = "g1_hiding_{}.bin")]
+    hiding_pattern: String,
+
+    /// Sync the first chunk of each curve to disk as soon as it's written, read it back, and check
+    /// with a pairing that it holds consecutive powers of tau, so that a broken environment aborts
+    /// the run within seconds instead of after hours. No further chunk of the curve is written
+    /// before the check passes.
+    #[arg(long, conflicts_with = "scratch")]
+    validate_first_chunk: bool,
+
+    /// After generation, check the compressed encodings of a sample of each set for anomalies
+    /// hinting at a serialization bug, e.g. a sign flag that's always set, and fail if any is
+    /// found.
+    #[arg(long, conflicts_with = "scratch")]
+    stats: bool,
+
+    /// After generation, time a commitment to a random polynomial with the first N G1 points, i.e.
+    /// a multi-scalar multiplication of N points, and report the throughput, e.g. to check that
+    /// the set is sized right for a prover. The commitment is checked against tau.
+    #[arg(long, value_name = "N", conflicts_with = "scratch")]
+    msm_bench: Option<usize>,
+
+    /// Sign the set fingerprint with the ed25519 key in the given file, which must contain the raw
+    /// 32-byte secret key, and write the signature next to the manifest, e.g. `params.sig.json`.
+    #[arg(long, value_name = "PATH", conflicts_with = "scratch")]
+    sign_key: Option<String>,
+
+    /// With `--sign-key`, also sign the hash of each chunk, so that chunks can be checked
+    /// individually.
+    #[arg(long, requires = "sign_key")]
+    sign_chunks: bool,
+
+    /// Print the chunk files the run would write, with their sizes, and exit without generating
+    /// anything.
+    #[arg(long, conflicts_with = "scratch")]
+    dry_run: bool,
+
+    /// Cap the total rate at which G1 and G2 points are computed, e.g. to stay within the thermal or
+    /// power budget of a laptop or shared machine.
+    #[arg(long, value_name = "N", value_parser = clap::value_parser!(u64).range(1..))]
+    max_points_per_second: Option<u64>,
+
+    /// Generate G2 on the main thread, while the G1 thread runs, instead of on a thread of its own
+    /// when it has at most 64 points, e.g. the 2 points of vanilla KZG.
+    #[arg(long)]
+    no_g2_thread_when_small: bool,
+
+    /// Before starting, estimate the compute time of the whole set on this machine with a
+    /// micro-benchmark, and refuse to start if it exceeds the given number of seconds, e.g. for
+    /// scheduled jobs that would be killed at their deadline.
+    #[arg(long, value_name = "SECS", conflicts_with = "dry_run")]
+    deadline: Option<u64>,
+
+    /// Start even if the estimate exceeds `--deadline`.
+    #[arg(long, requires = "deadline")]
+    override_deadline: bool,
+
+    /// Serve `GET /healthz` on the given address, e.g. `0.0.0.0:8080`: 200 while the progress
+    /// counters advance, 503 once they have been stuck for `--health-stall-secs`.
+    #[arg(long, value_name = "ADDR")]
+    health_addr: Option<String>,
+
+    /// Seconds without progress after which `/healthz` reports the run as stalled. Must cover the
+    /// phases that don't advance the counters, like building indices or verifying.
+    #[arg(
+        long,
+        value_name = "SECS",
+        default_value = "300",
+        requires = "health_addr"
+    )]
+    health_stall_secs: u64,
+
+    /// For environments with flaky hardware: after generation, verify the whole content of the set
+    /// with pairings, and if anything is inconsistent, e.g. a point miscomputed by a transient
+    /// fault, discard the output and generate again with a fresh tau, up to N more times.
+    /// Verifying costs about as much as generating.
+    #[arg(
+        long,
+        value_name = "N",
+        conflicts_with_all = ["scratch", "resume", "spot_mode", "tau_source"]
+    )]
+    verify_and_retry: Option<usize>,
+
+    /// Test hook: miscompute a point of the first G1 chunk in the first N attempts of
+    /// `--verify-and-retry`.
+    #[arg(long, value_name = "N", hide = true, requires = "verify_and_retry")]
+    inject_fault_attempts: Option<usize>,
+
+    /// Test hook: exit abruptly right after writing chunk K of either curve, to exercise resuming.
+    /// Only in debug builds, so that release binaries can't be told to abort mid-set.
+    #[cfg(debug_assertions)]
+    #[arg(long, value_name = "K", hide = true)]
+    crash_after_chunk: Option<usize>,
+
+    /// Test hook: panic on the G1 generator thread, to exercise shutting down the other threads.
+    #[arg(long, hide = true)]
+    panic_in_g1: bool,
+
+    #[command(flatten)]
+    tau: TauArgs,
+
+    /// Write tau, encrypted with the passphrase in the variable named by `--passphrase-env`, to
+    /// the given file, so that a later run can generate the other group with the same tau through
+    /// `--tau-sealed`, e.g. G1 with `--g2-count 0` now and G2 with `--g1-count 0` and another
+    /// `--manifest` later. Delete the file once both are done.
+    #[arg(long, value_name = "PATH", requires = "passphrase_env")]
+    tau_encrypt: Option<String>,
+
+    /// Path of the set manifest. In scratch mode, the scratch manifest is written next to it
+    /// instead, e.g. `params.scratch.json`, and `finalize` writes it.
+    #[arg(long, default_value = "params.json")]
+    manifest: String,
+
+    /// Also write views of the first 2^K points for each of the given comma-separated Ks, e.g.
+    /// `params.view-10.json` for K=10 with the default manifest path. Views share the chunk files
+    /// of the full set.
+    #[arg(
+        long,
+        value_name = "K",
+        value_delimiter = ',',
+        conflicts_with = "scratch"
+    )]
+    degree_views: Vec<u32>,
+
+    /// File descriptor to write the progress line to, e.g. 2 for stderr.
+    #[arg(long, default_value = "1")]
+    progress_fd: i32,
+
+    /// File descriptor to write the log to as JSON lines, e.g. 3. The log is written as plain text
+    /// to stdout if unspecified.
+    #[arg(long)]
+    log_fd: Option<i32>,
+}
+
+impl GenerateArgs {
+    fn tau(&self) -> Result<SecretScalar> {
+        if self.resume || self.spot_mode {
+            return self.tau.get_required();
+        }
+        match self.tau.get()? {
+            Some(tau) => Ok(tau),
+            None => tau::get_random_scalar().context(
+                "sampling a random tau; pass one with --tau, --tau-env, --seed-hex, --seed-env, \
+                 --seed-from-file, or --tau-sealed instead",
+            ),
+        }
+    }
+
+    /// Returns the offset and stride of the powers to generate, see `--even-powers`.
+    fn powers(&self) -> (u64, u64) {
+        match (self.even_powers, self.odd_powers) {
+            (true, _) => (0, 2),
+            (_, true) => (1, 2),
+            _ => (0, 1),
+        }
+    }
+
+    fn g1_config(&self) -> CurveConfig {
+        let (offset, stride) = self.powers();
+        CurveConfig {
+            layout: Layout {
+                count: self.g1_count as u64,
+                chunk_length: self.g1_chunk_length as u64,
+                pattern: self.g1_pattern.clone(),
+                encoding: self.g1_encoding,
+                format: self.format,
+                format_version: self.format_version,
+                offset,
+                stride,
+                block_size: self.block_size,
+                generator: (self.g1_generator != "standard").then(|| self.g1_generator.clone()),
+                reverse: self.reverse,
+            },
+            scratch: self.scratch,
+            resume: self.resume || self.spot_mode,
+            index_path: self
+                .emit_index
+                .then(|| index::path::<G1>(self.manifest.as_str())),
+            record_provenance: self.record_provenance,
+            validate_first_chunk: self.validate_first_chunk,
+            stats: self.stats,
+            hiding_pattern: self.hiding.then(|| self.hiding_pattern.clone()),
+            #[cfg(debug_assertions)]
+            crash_after_chunk: self.crash_after_chunk,
+            inject_fault: false,
+            panic: self.panic_in_g1,
+        }
+    }
+
+    fn g2_config(&self) -> CurveConfig {
+        let (offset, stride) = self.powers();
+        CurveConfig {
+            layout: Layout {
+                count: self.g2_count as u64,
+                chunk_length: self.g2_chunk_length as u64,
+                pattern: self.g2_pattern.clone(),
+                encoding: self.g2_encoding,
+                format: self.format,
+                format_version: self.format_version,
+                offset,
+                stride,
+                block_size: self.block_size,
+                generator: (self.g2_generator != "standard").then(|| self.g2_generator.clone()),
+                reverse: self.reverse,
+            },
+            scratch: self.scratch,
+            resume: self.resume || self.spot_mode,
+            index_path: self
+                .emit_index
+                .then(|| index::path::<G2>(self.manifest.as_str())),
+            record_provenance: self.record_provenance,
+            validate_first_chunk: self.validate_first_chunk,
+            stats: self.stats,
+            hiding_pattern: None,
+            #[cfg(debug_assertions)]
+            crash_after_chunk: self.crash_after_chunk,
+            inject_fault: false,
+            panic: false,
+        }
+    }
 }
 
-fn get_random_scalar() -> Scalar {
-    let mut bytes = [0u8; 64];
-    getrandom::fill(&mut bytes).unwrap();
-    let scalar = DuskScalar::from_bytes_wide(&bytes);
-    Scalar::from_bytes_le(&scalar.to_bytes())
-        .into_option()
-        .unwrap()
+#[derive(clap::Args, Debug)]
+struct FinalizeArgs {
+    /// Scratch files to finalize.
+    #[arg(
+        required_unless_present = "scratch_manifest",
+        conflicts_with = "scratch_manifest"
+    )]
+    files: Vec<String>,
+
+    /// Finalize the scratch files listed in the given scratch manifest, e.g.
+    /// `params.scratch.json`, with the layout it records instead of the options below, and write
+    /// the set manifest to `--manifest`.
+    #[arg(long, value_name = "PATH")]
+    scratch_manifest: Option<String>,
+
+    /// Path of the set manifest written with `--scratch-manifest`.
+    #[arg(long, default_value = "params.json")]
+    manifest: String,
+
+    /// Output file pattern for G1 chunks.
+    #[arg(long, default_value = "g1_{}.bin")]
+    g1_pattern: String,
+
+    /// Output file pattern for G2 chunks.
+    #[arg(long, default_value = "g2_{}.bin")]
+    g2_pattern: String,
+
+    /// Encoding of G1 points.
+    #[arg(long, value_enum, default_value_t = Encoding::Compressed)]
+    g1_encoding: Encoding,
+
+    /// Encoding of G2 points.
+    #[arg(long, value_enum, default_value_t = Encoding::Compressed)]
+    g2_encoding: Encoding,
+
+    /// Container format of chunk files. CBOR and MessagePack are self-describing and readable from
+    /// other languages, at the cost of a few bytes per point.
+    #[arg(long, value_enum, default_value_t = Format::Bincode)]
+    format: Format,
+
+    /// Version of the chunk file format, e.g. 1 to match the chunks of a set written in version 1.
+    #[arg(
+        long,
+        value_name = "N",
+        default_value_t = chunk::FORMAT_VERSION,
+        value_parser = clap::value_parser!(u8).range(1..=chunk::FORMAT_VERSION as i64)
+    )]
+    format_version: u8,
+}
+
+#[derive(clap::Args, Debug)]
+struct VanishingCommitmentArgs {
+    /// Size of the evaluation domain.
+    #[arg(long)]
+    n: u64,
+
+    /// Path of the set manifest.
+    #[arg(long, default_value = "params.json")]
+    manifest: String,
+}
+
+#[derive(clap::Args, Debug)]
+struct RepairChunkArgs {
+    /// Curve of the chunk to repair.
+    #[arg(long, value_enum)]
+    curve: Curve,
+
+    /// Index of the chunk to repair.
+    #[arg(long)]
+    index: usize,
+
+    /// Path of the set manifest.
+    #[arg(long, default_value = "params.json")]
+    manifest: String,
+
+    #[command(flatten)]
+    tau: TauArgs,
+}
+
+#[derive(clap::Args, Debug)]
+struct ValidateArgs {
+    /// Also check the hash of each chunk file against the manifest, which reads the whole set.
+    #[arg(long)]
+    check_hashes: bool,
+
+    /// Also check the hashes and that every point is a valid group element, in the prime-order
+    /// subgroup. The subgroup checks make this much slower than `--check-hashes`.
+    #[arg(long)]
+    check_points: bool,
+
+    /// With `--check-points`, skip the point checks if the marker at the given path records that
+    /// the set passed them, checking the hashes only, and write the marker if it passes them now.
+    /// Anyone who can write the marker can make validations skip the point checks.
+    #[arg(long, value_name = "PATH", requires = "check_points")]
+    marker: Option<String>,
+
+    /// Write the chunks with problems to the given file, to be recomputed with `repair`.
+    #[arg(long, value_name = "PATH")]
+    failures: Option<String>,
+
+    /// Path of the set manifest.
+    #[arg(long, default_value = "params.json")]
+    manifest: String,
+}
+
+#[derive(clap::Args, Debug)]
+struct RepairArgs {
+    /// Failure list written by `validate --failures`.
+    #[arg(long, value_name = "PATH")]
+    repair_from_verify: String,
+
+    /// Path of the set manifest.
+    #[arg(long, default_value = "params.json")]
+    manifest: String,
+
+    #[command(flatten)]
+    tau: TauArgs,
+}
+
+#[derive(clap::Args, Debug)]
+struct LookupArgs {
+    /// Curve of the point.
+    #[arg(long, value_enum)]
+    curve: Curve,
+
+    /// The point to look up, compressed and hex-encoded.
+    #[arg(long)]
+    point: String,
+
+    /// Path of the set manifest.
+    #[arg(long, default_value = "params.json")]
+    manifest: String,
+}
+
+#[derive(clap::Args, Debug)]
+struct EqualityProofArgs {
+    /// Hex-encoded challenge nonce, as printed by `equality-challenge`.
+    #[arg(long)]
+    nonce: String,
+
+    /// Number of points to sample from each curve. Both parties must use the same value.
+    #[arg(long, default_value = "64")]
+    samples: usize,
+
+    /// Path of the set manifest.
+    #[arg(long, default_value = "params.json")]
+    manifest: String,
+}
+
+#[derive(clap::Args, Debug)]
+struct DownsampleArgs {
+    /// Distance between the indices of the kept points.
+    #[arg(long)]
+    stride: u64,
+
+    /// Index of the first kept point.
+    #[arg(long, default_value = "0")]
+    offset: u64,
+
+    /// Path of the manifest of the source set.
+    #[arg(long, default_value = "params.json")]
+    manifest: String,
+
+    /// Path of the manifest of the strided set.
+    #[arg(long, default_value = "params.downsampled.json")]
+    output: String,
+
+    /// File pattern of the strided G1 chunks, with the same placeholders as `--g1-pattern`.
+    #[arg(long, default_value = "g1_downsampled_{}.bin")]
+    g1_pattern: String,
+
+    /// File pattern of the strided G2 chunks, with the same placeholders as `--g2-pattern`.
+    #[arg(long, default_value = "g2_downsampled_{}.bin")]
+    g2_pattern: String,
+}
+
+#[derive(clap::Args, Debug)]
+struct RelayoutArgs {
+    /// New file pattern for G1 chunks, the current one if not given.
+    #[arg(long)]
+    g1_pattern: Option<String>,
+
+    /// New file pattern for G2 chunks, the current one if not given.
+    #[arg(long)]
+    g2_pattern: Option<String>,
+
+    /// New number of G1 points per chunk, the current one if not given.
+    #[arg(long)]
+    g1_chunk_length: Option<u64>,
+
+    /// New number of G2 points per chunk, the current one if not given.
+    #[arg(long)]
+    g2_chunk_length: Option<u64>,
+
+    /// Keep the old files, hard-linking them instead of renaming them.
+    #[arg(long)]
+    keep: bool,
+
+    /// Path of the set manifest.
+    #[arg(long, default_value = "params.json")]
+    manifest: String,
+}
+
+#[derive(clap::Args, Debug)]
+struct VerifySignatureArgs {
+    /// Hex-encoded ed25519 public key of the expected signer.
+    #[arg(long)]
+    pubkey: String,
+
+    /// Path of the set manifest.
+    #[arg(long, default_value = "params.json")]
+    manifest: String,
+
+    /// Path of the signatures, derived from the manifest path if not given, e.g.
+    /// `params.sig.json`.
+    #[arg(long)]
+    signatures: Option<String>,
+}
+
+#[derive(clap::Args, Debug)]
+struct ManifestDiffArgs {
+    /// Path of the first manifest.
+    a: String,
+
+    /// Path of the second manifest.
+    b: String,
+}
+
+#[derive(clap::Args, Debug)]
+struct ExplainSecurityArgs {
+    /// Path of the set manifest.
+    #[arg(long, default_value = "params.json")]
+    manifest: String,
+}
+
+#[derive(clap::Args, Debug)]
+struct VerifyAgainstCeremonyArgs {
+    /// Hex-encoded compressed tau·G1 published by the ceremony.
+    #[arg(long)]
+    tau_g1: String,
+
+    /// Hex-encoded compressed tau·G2 published by the ceremony.
+    #[arg(long)]
+    tau_g2: String,
+
+    /// Path of the set manifest.
+    #[arg(long, default_value = "params.json")]
+    manifest: String,
+
+    /// Record the ceremony in the manifest if the set holds its tau, for `explain-security`.
+    #[arg(long)]
+    record: bool,
+
+    /// Number of contributions to the ceremony, recorded with `--record`.
+    #[arg(long, value_name = "N", requires = "record")]
+    contributions: Option<u64>,
+}
+
+#[derive(clap::Args, Debug)]
+struct MakeTestBundleArgs {
+    /// Number of polynomials to commit to and open.
+    #[arg(long, default_value = "8")]
+    num: usize,
+
+    /// Degree of the polynomials, which needs as many G1 powers plus one.
+    #[arg(long, default_value = "15")]
+    degree: usize,
+
+    /// Path of the set manifest.
+    #[arg(long, default_value = "params.json")]
+    manifest: String,
+
+    /// Path of the bundle.
+    #[arg(long, default_value = "params.bundle.json")]
+    output: String,
+}
+
+#[derive(clap::Args, Debug)]
+struct VerifyBundleArgs {
+    /// Path of the bundle.
+    #[arg(long, default_value = "params.bundle.json")]
+    bundle: String,
+
+    /// Check the openings against the verifier key written by `trim-verifier-key` at the given path
+    /// instead of the one in the bundle.
+    #[arg(long, value_name = "PATH")]
+    verifier_key: Option<String>,
+}
+
+#[derive(clap::Args, Debug)]
+struct BundleArgs {
+    /// Directory to write the bundle to, with the sets in its `base` and `shifted` subdirectories.
+    #[arg(long, default_value = "bundle")]
+    dir: String,
+
+    /// Number of G1 points of each set.
+    #[arg(long)]
+    g1_count: u64,
+
+    /// Number of G2 points of each set.
+    #[arg(long, default_value = "2")]
+    g2_count: u64,
+
+    /// Power of tau the shifted set starts at, less than the G1 count.
+    #[arg(long, value_name = "K")]
+    shift: u64,
+
+    /// Number of points in each chunk.
+    #[arg(long, default_value = "65536")]
+    chunk_length: u64,
+
+    #[command(flatten)]
+    tau: TauArgs,
+}
+
+#[derive(clap::Args, Debug)]
+struct VerifyBundleConsistencyArgs {
+    /// Directory of the bundle.
+    #[arg(long, default_value = "bundle")]
+    dir: String,
+}
+
+#[derive(clap::Args, Debug)]
+struct TrimVerifierKeyArgs {
+    /// Path of the set manifest.
+    #[arg(long, default_value = "params.json")]
+    manifest: String,
+
+    /// Path of the verifier key.
+    #[arg(long, default_value = "params.vk")]
+    output: String,
+}
+
+#[derive(clap::Args, Debug)]
+struct CheckDegreeArgs {
+    /// Degree of the polynomials to commit to, which takes `need + 1` G1 powers.
+    #[arg(long, value_name = "D")]
+    need: u64,
+
+    /// Path of the set manifest.
+    #[arg(long, default_value = "params.json")]
+    manifest: String,
+}
+
+#[derive(clap::Args, Debug)]
+struct PrecomputeMsmArgs {
+    /// Width in bits of the scalar windows. The table holds ceil(255 / window) points per power.
+    #[arg(long)]
+    window: u32,
+
+    /// Number of powers to precompute, all of them if not given.
+    #[arg(long)]
+    count: Option<u64>,
+
+    /// Path of the set manifest.
+    #[arg(long, default_value = "params.json")]
+    manifest: String,
+
+    /// Path of the table.
+    #[arg(long, default_value = "g1_msm_table.bin")]
+    output: String,
+}
+
+/// A chunk sent from a compute thread to its writer thread.
+struct ComputedChunk<P> {
+    index: usize,
+    points: Vec<P>,
+    /// Whether `points` are only the first points of the chunk, to checkpoint.
+    partial: bool,
+}
+
+/// What to generate for a single curve.
+#[derive(Debug, Clone)]
+struct CurveConfig {
+    layout: Layout,
+    scratch: bool,
+    resume: bool,
+    /// Path of the index to write, see `--emit-index`.
+    index_path: Option<String>,
+    record_provenance: bool,
+    validate_first_chunk: bool,
+    stats: bool,
+    /// File pattern of the hiding key, if one is to be generated.
+    hiding_pattern: Option<String>,
+    #[cfg(debug_assertions)]
+    crash_after_chunk: Option<usize>,
+    /// Corrupt the last point of the first chunk, see `--inject-fault-attempts`.
+    inject_fault: bool,
+    /// Panic before computing anything, see `--panic-in-g1`.
+    panic: bool,
 }
 
 #[derive(Debug)]
 struct Generator {
-    tau: Scalar,
-    g1_count: AtomicUsize,
-    g2_count: AtomicUsize,
-    print_mutex: Mutex<()>,
+    tau: SecretScalar,
+    g1_progress: CurveProgress,
+    g2_progress: CurveProgress,
+    done: AtomicBool,
+    /// Set by signal handlers in spot mode to stop generation at the next point.
+    stop: Arc<AtomicBool>,
+    /// Set when the generation of either curve fails or panics, to stop the other one at the next
+    /// point.
+    cancelled: AtomicBool,
+    rate_limiter: Option<RateLimiter>,
+    /// Fed with the progress counters by the reporter, see `--health-addr`.
+    health: Option<Arc<Health>>,
+    /// Shared by the attempts of `--verify-and-retry`.
+    output: Arc<Output>,
+    manifest: Mutex<Manifest>,
     reporter_handle: Mutex<Option<JoinHandle<Result<()>>>>,
     g1_generator_handle: Mutex<Option<JoinHandle<Result<()>>>>,
     g2_generator_handle: Mutex<Option<JoinHandle<Result<()>>>>,
+    /// Result of the G2 generation when it ran on the main thread, see `--no-g2-thread-when-small`.
+    g2_inline_result: Mutex<Option<Result<()>>>,
+}
+
+/// Lives on a generator thread and cancels the other threads if it panics, so that `join_all`
+/// surfaces the panic promptly instead of waiting for the other curve while the reporter prints
+/// stale counts.
+struct PanicGuard<'a>(&'a Generator);
+
+impl Drop for PanicGuard<'_> {
+    fn drop(&mut self) {
+        if std::thread::panicking() {
+            self.0.cancelled.store(true, Ordering::Relaxed);
+            self.0.done.store(true, Ordering::Release);
+        }
+    }
+}
+
+/// Error of a curve whose generation was stopped because the other one failed.
+#[derive(Debug)]
+struct Cancelled(&'static str);
+
+impl std::fmt::Display for Cancelled {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} generation cancelled", self.0)
+    }
+}
+
+impl std::error::Error for Cancelled {}
+
+/// Extracts the message of a panic payload.
+fn panic_message(payload: &(dyn std::any::Any + Send)) -> &str {
+    if let Some(message) = payload.downcast_ref::<&str>() {
+        message
+    } else if let Some(message) = payload.downcast_ref::<String>() {
+        message.as_str()
+    } else {
+        "unknown panic payload"
+    }
 }
 
 impl Generator {
@@ -67,96 +1148,245 @@ impl Generator {
         let mut handle = generator.reporter_handle.lock().unwrap();
         *handle = Some(std::thread::spawn(move || {
             let start = Instant::now();
-            loop {
+            let mut previous = (start, 0);
+            while !self.done.load(Ordering::Acquire) {
                 std::thread::sleep(Duration::from_secs(1));
-                print!(
-                    "\r{} G1 pts and {} G2 pts generated in {} seconds",
-                    self.g1_count.load(Ordering::Acquire),
-                    self.g2_count.load(Ordering::Acquire),
-                    (Instant::now() - start).as_secs()
-                );
-                std::io::stdout().flush().unwrap();
+                let now = Instant::now();
+                let elapsed = now - start;
+                let g1 = self.g1_progress.snapshot();
+                let g2 = self.g2_progress.snapshot();
+                if let Some(health) = &self.health {
+                    health.observe(g1.computed + g1.written + g2.computed + g2.written);
+                }
+                let etas = Etas::new(&g1, elapsed).max(Etas::new(&g2, elapsed));
+                let rate = match &self.rate_limiter {
+                    Some(limiter) => {
+                        let computed = g1.computed + g2.computed;
+                        let rate = (computed - previous.1.min(computed)) as f64
+                            / (now - previous.0).as_secs_f64();
+                        previous = (now, computed);
+                        format!(
+                            ", {:.0} pts/s (capped at {})",
+                            rate,
+                            limiter.points_per_second()
+                        )
+                    }
+                    None => String::new(),
+                };
+                self.output.progress(format!(
+                    "{}/{} G1 pts and {}/{} G2 pts generated in {} seconds, compute ETA {}, writes ETA {} ({} chunks queued){}",
+                    g1.computed,
+                    g1.total,
+                    g2.computed,
+                    g2.total,
+                    elapsed.as_secs(),
+                    progress::format_eta(etas.compute),
+                    progress::format_eta(etas.write),
+                    g1.queued_chunks + g2.queued_chunks,
+                    rate,
+                ))?;
             }
+            self.output.finish_progress()?;
+            Ok(())
         }));
     }
 
-    fn new() -> Pin<Arc<Self>> {
+    fn new(
+        tau: SecretScalar,
+        rate_limiter: Option<RateLimiter>,
+        health: Option<Arc<Health>>,
+        output: Arc<Output>,
+    ) -> Pin<Arc<Self>> {
         let reporter = Arc::pin(Self {
-            tau: get_random_scalar(),
-            g1_count: AtomicUsize::new(0),
-            g2_count: AtomicUsize::new(0),
-            print_mutex: Mutex::default(),
+            tau,
+            g1_progress: CurveProgress::default(),
+            g2_progress: CurveProgress::default(),
+            done: AtomicBool::new(false),
+            stop: Arc::default(),
+            cancelled: AtomicBool::new(false),
+            rate_limiter,
+            health,
+            output,
+            manifest: Mutex::default(),
             reporter_handle: Mutex::default(),
             g1_generator_handle: Mutex::default(),
             g2_generator_handle: Mutex::default(),
+            g2_inline_result: Mutex::default(),
         });
         reporter.clone().start_reporting();
         reporter
     }
 
-    fn println(&self, s: impl AsRef<str>) {
-        let _lock = self.print_mutex.lock().unwrap();
-        println!("{}", s.as_ref());
+    /// Implements `--verify-and-retry`: checks the content of the generated sets against tau.
+    /// Returns one message per problem found.
+    fn verify(&self) -> Result<Vec<String>> {
+        let manifest = self.manifest.lock().unwrap();
+        let mut problems = vec![];
+        for (name, curve) in &manifest.curves {
+            self.output.log(format!("Verifying {}...", name))?;
+            let curve_problems = match name.as_str() {
+                G1::NAME => validate::check_powers::<G1>(curve, &self.tau)?,
+                G2::NAME => validate::check_powers::<G2>(curve, &self.tau)?,
+                _ => vec![unknown_curve()],
+            };
+            problems.extend(
+                curve_problems
+                    .into_iter()
+                    .map(|problem| format!("{}: {}", name, problem)),
+            );
+        }
+        Ok(problems)
     }
 
-    fn generate_g1(
-        self: Pin<Arc<Self>>,
-        count: usize,
-        pattern: &str,
-        chunk_length: usize,
-    ) -> Result<()> {
-        if count > MAX_COUNT {
+    /// Implements `--msm-bench`.
+    fn msm_bench(&self, points: usize) -> Result<()> {
+        let manifest = self.manifest.lock().unwrap();
+        let layout = &manifest
+            .curves
+            .get(G1::NAME)
+            .ok_or_else(|| anyhow!("no {} set was generated", G1::NAME))?
+            .layout;
+        let bench = msm::bench::<G1>(layout, &self.tau, points)?;
+        if !bench.correct {
             return Err(anyhow!(
-                "invalid number of entries requested: {} (must be at most {})",
-                count,
-                MAX_COUNT
+                "the MSM benchmark commitment doesn't match tau, the set is inconsistent"
             ));
         }
-        if chunk_length < 2 {
-            return Err(anyhow!("each chunk must have at least 2 elements"));
+        self.output.log(format!(
+            "MSM of {} G1 points in {:.3} seconds ({:.0} pts/s), commitment checked",
+            bench.points,
+            bench.elapsed.as_secs_f64(),
+            bench.points_per_second()
+        ))?;
+        Ok(())
+    }
+
+    /// Writes the manifest, views, and signatures of a successful run.
+    fn finish(&self, args: &GenerateArgs, signing_key: Option<&SigningKey>) -> Result<()> {
+        let manifest = self.manifest.lock().unwrap();
+        if args.scratch {
+            let path = scratch::manifest_path(args.manifest.as_str());
+            ScratchManifest::new(&manifest)?.save(path.as_str())?;
+            self.output.log(format!("{} written", path))?;
+            return Ok(());
+        }
+        manifest.save(args.manifest.as_str())?;
+        self.output.log(format!("{} written", args.manifest))?;
+        for &log_count in &args.degree_views {
+            let path = view::path(args.manifest.as_str(), log_count);
+            View::new(&manifest, args.manifest.as_str(), log_count)?.save(path.as_str())?;
+            self.output.log(format!("{} written", path))?;
+        }
+        if let Some(key) = signing_key {
+            let signatures = Signatures::sign(&manifest, key, args.sign_chunks);
+            let path = signature::path(args.manifest.as_str());
+            signatures.save(path.as_str())?;
+            self.output.log(format!(
+                "{} written, signed by {}",
+                path, signatures.public_key
+            ))?;
         }
+        Ok(())
+    }
 
-        self.println(format!("Generating {} G1 points...", count));
+    /// Implements `--validate-first-chunk`: syncs the first chunk to disk, reads it back, and checks
+    /// with a pairing that it holds consecutive powers of tau starting from the first power of the
+    /// layout.
+    fn check_first_chunk<P: Point>(&self, layout: &Layout, path: &str) -> Result<()> {
+        std::fs::File::open(path)
+            .and_then(|file| file.sync_all())
+            .with_context(|| format!("syncing {}", path))?;
+        let points = chunk::read::<P>(path)?;
+        let first = layout.compute::<P>(&self.tau, 0, 1)?;
+        let step = SecretScalar::new(layout.step(&self.tau));
+        if points.first() != first.first() || !P::check_powers(&points, &step)? {
+            return Err(anyhow!(
+                "{} doesn't hold consecutive powers of tau once read back, the environment may be \
+                 broken",
+                path
+            ));
+        }
+        self.output.log(format!("{} validated", path))?;
+        Ok(())
+    }
 
-        let mut chunk = vec![H384::zero(); chunk_length];
-        let mut g = G1Projective::generator();
-        loop {
-            let index = self.g1_count.fetch_add(1, Ordering::AcqRel);
-            if index >= MAX_COUNT {
-                return Ok(());
+    fn write_chunks<P: Point>(
+        &self,
+        progress: &CurveProgress,
+        chunks: mpsc::Receiver<ComputedChunk<P>>,
+        config: &CurveConfig,
+    ) -> Result<Vec<ChunkEntry>> {
+        let mut entries = vec![];
+        for ComputedChunk {
+            index: chunk_index,
+            points: mut chunk,
+            partial,
+        } in chunks
+        {
+            if config.inject_fault && chunk_index == 0 && !partial {
+                let last = chunk.len() - 1;
+                chunk[last] = chunk[last].double();
+            }
+            let layout = &config.layout;
+            let path = layout.chunk_path::<P>(chunk_index as u64)?;
+            let first_index = layout.chunk_start(chunk_index as u64);
+            let partial_path = resume::partial_path::<P>(layout, chunk_index as u64)?;
+            if partial {
+                scratch::write(partial_path.as_str(), chunk_index, first_index, &chunk)?;
+                self.output.log(format!(
+                    "{} points of {} chunk {} checkpointed to {}",
+                    chunk.len(),
+                    P::NAME,
+                    chunk_index,
+                    partial_path
+                ))?;
+                continue;
             }
-            g *= self.tau;
-            chunk[index % chunk_length] = H384::from_slice(g.to_bytes().as_ref());
-            if index % chunk_length == chunk_length - 1 {
-                let chunk_index = index / chunk_length;
-                let path = pattern.replace("{}", chunk_index.to_string().as_str());
-                {
-                    let mut file = File::create(path.as_str())?;
-                    bincode::serde::encode_into_std_write(
+            if config.scratch {
+                scratch::write(path.as_str(), chunk_index, first_index, &chunk)?;
+            } else {
+                entries.push(ChunkEntry {
+                    index: chunk_index as u64,
+                    path: path.clone(),
+                    count: chunk.len() as u64,
+                    blake2b: chunk::write(
+                        path.as_str(),
                         &chunk,
-                        &mut file,
-                        bincode::config::standard(),
-                    )?;
-                }
-                self.println(format!("\n{} written", path));
+                        layout.format,
+                        layout.format_version,
+                        layout.block_size,
+                        layout.encoding,
+                        first_index,
+                    )?,
+                    provenance: config.record_provenance.then(manifest::Provenance::current),
+                });
+            }
+            if config.validate_first_chunk && chunk_index == 0 {
+                self.check_first_chunk::<P>(layout, path.as_str())?;
+            }
+            if config.resume {
+                // The checkpoint of this chunk, if any, is obsolete.
+                let _ = std::fs::remove_file(partial_path.as_str());
+            }
+            progress.queued_chunks.fetch_sub(1, Ordering::AcqRel);
+            progress.written.fetch_add(chunk.len(), Ordering::AcqRel);
+            self.output.log(format!("{} written", path))?;
+            #[cfg(debug_assertions)]
+            if config.crash_after_chunk == Some(chunk_index) {
+                self.output.log(format!(
+                    "Exiting after {} chunk {} (--crash-after-chunk)",
+                    P::NAME,
+                    chunk_index
+                ))?;
+                std::process::exit(1);
             }
         }
+        Ok(entries)
     }
 
-    fn start_generate_g1(self: Pin<Arc<Self>>, count: usize, pattern: String, chunk_length: usize) {
-        let generator = self.clone();
-        let mut handle = generator.g1_generator_handle.lock().unwrap();
-        *handle = Some(std::thread::spawn(move || {
-            self.generate_g1(count, pattern.as_str(), chunk_length)
-        }));
-    }
-
-    fn generate_g2(
-        self: Pin<Arc<Self>>,
-        count: usize,
-        pattern: &str,
-        chunk_length: usize,
-    ) -> Result<()> {
+    fn generate<P: Point>(&self, progress: &CurveProgress, config: &CurveConfig) -> Result<()> {
+        let count = config.layout.count as usize;
+        let chunk_length = config.layout.chunk_length as usize;
         if count > MAX_COUNT {
             return Err(anyhow!(
                 "invalid number of entries requested: {} (must be at most {})",
@@ -167,84 +1397,1179 @@ impl Generator {
         if chunk_length < 2 {
             return Err(anyhow!("each chunk must have at least 2 elements"));
         }
+        if config.panic {
+            panic!("{} generation panicked (--panic-in-g1)", P::NAME);
+        }
+        // Fail on an invalid pattern before computing anything.
+        config
+            .layout
+            .chunk_path::<P>(0)
+            .with_context(|| format!("invalid {} file pattern", P::NAME))?;
+
+        let mut config = config.clone();
+        if config.resume
+            && let Some(version) = resume::format_version::<P>(&config.layout)?
+            && version != config.layout.format_version
+        {
+            self.output.log(format!(
+                "Resuming {} in chunk format version {} like the existing chunks",
+                P::NAME,
+                version
+            ))?;
+            config.layout.format_version = version;
+        }
+        let config = &config;
 
-        self.println(format!("Generating {} G2 points...", count));
+        let mut manifest = CurveManifest {
+            layout: config.layout.clone(),
+            chunks: vec![],
+            index_file: None,
+            hiding: None,
+        };
 
-        let mut chunk = vec![H768::zero(); chunk_length];
-        let mut g = G2Projective::generator();
-        loop {
-            let index = self.g2_count.fetch_add(1, Ordering::AcqRel);
-            if index >= MAX_COUNT {
-                return Ok(());
+        if count == 0 {
+            self.output
+                .log(format!("No {} points requested, no files written", P::NAME))?;
+        } else {
+            manifest.chunks = self.compute::<P>(progress, config)?;
+            if let Some(path) = &config.index_path {
+                self.output.log(format!("Building {}...", path))?;
+                index::build::<P>(&manifest.layout, path.as_str())?;
+                self.output.log(format!("{} written", path))?;
+                manifest.index_file = Some(path.clone());
             }
-            g *= self.tau;
-            chunk[index % chunk_length] = H768::from_slice(g.to_bytes().as_ref());
-            if index % chunk_length == chunk_length - 1 {
-                let chunk_index = index / chunk_length;
-                let path = pattern.replace("{}", chunk_index.to_string().as_str());
-                {
-                    let mut file = File::create(path.as_str())?;
-                    bincode::serde::encode_into_std_write(
-                        &chunk,
-                        &mut file,
-                        bincode::config::standard(),
-                    )?;
+            if let Some(pattern) = &config.hiding_pattern {
+                self.output
+                    .log(format!("Generating the {} hiding key...", P::NAME))?;
+                manifest.hiding = Some(hiding::build::<P>(
+                    &self.tau,
+                    &manifest.layout,
+                    pattern.as_str(),
+                )?);
+            }
+            if config.stats {
+                let stats = stats::sample::<P>(&manifest.layout, STATS_SAMPLES)?;
+                self.output.log(format!(
+                    "{} stats: {} points sampled, {:.1}% with the sign flag set",
+                    P::NAME,
+                    stats.samples,
+                    100.0 * stats.sign as f64 / stats.samples as f64
+                ))?;
+                let anomalies = stats.anomalies();
+                if !anomalies.is_empty() {
+                    return Err(anyhow!(
+                        "anomalous {} sample, the serialization may be broken: {}",
+                        P::NAME,
+                        anomalies.join(", ")
+                    ));
                 }
-                self.println(format!("\n{} written", path));
             }
         }
+
+        let mut set_manifest = self.manifest.lock().unwrap();
+        set_manifest.curves.insert(P::NAME.to_string(), manifest);
+        Ok(())
     }
 
-    fn start_generate_g2(self: Pin<Arc<Self>>, count: usize, pattern: String, chunk_length: usize) {
+    fn compute<P: Point>(
+        &self,
+        progress: &CurveProgress,
+        config: &CurveConfig,
+    ) -> Result<Vec<ChunkEntry>> {
+        let count = config.layout.count as usize;
+        let chunk_length = config.layout.chunk_length as usize;
+        let base = config.layout.base::<P>()?;
+
+        self.output
+            .log(format!("Generating {} {} points...", count, P::NAME))?;
+        progress.total.store(count, Ordering::Release);
+
+        let mut entries = vec![];
+        let mut chunk = Vec::with_capacity(chunk_length);
+        if config.resume {
+            entries = resume::complete_chunks::<P>(&self.tau, &config.layout)?;
+            chunk.extend(resume::partial_chunk::<P>(
+                &self.tau,
+                &config.layout,
+                entries.len() as u64,
+            )?);
+            self.output.log(format!(
+                "Resuming {} after {} complete chunks and {} checkpointed points",
+                P::NAME,
+                entries.len(),
+                chunk.len()
+            ))?;
+        }
+        let start = count.min(entries.len() * chunk_length + chunk.len());
+        progress.computed.store(start, Ordering::Release);
+        progress
+            .written
+            .store(start - chunk.len(), Ordering::Release);
+
+        let stopped = std::thread::scope(|scope| {
+            let (sender, receiver) = mpsc::sync_channel(WRITE_QUEUE_LENGTH);
+            let writer = scope.spawn(|| self.write_chunks(progress, receiver, config));
+
+            let layout = &config.layout;
+            let step = SecretScalar::new(layout.step(&self.tau));
+            // Nothing is left to compute if every chunk was resumed, and there's no point at
+            // `start` then, whose power would underflow in reverse sets.
+            let mut g = if start < count {
+                base * powers::power(&self.tau, layout.power(start as u64))
+            } else {
+                base
+            };
+            let mut stopped = false;
+            for index in start..count {
+                if self.stop.load(Ordering::Relaxed) || self.cancelled.load(Ordering::Relaxed) {
+                    stopped = true;
+                    if !chunk.is_empty() {
+                        let _ = sender.send(ComputedChunk {
+                            index: index / chunk_length,
+                            points: std::mem::take(&mut chunk),
+                            partial: true,
+                        });
+                    }
+                    break;
+                }
+                if let Some(limiter) = &self.rate_limiter {
+                    limiter.acquire();
+                }
+                chunk.push(g);
+                g *= *step;
+                progress.computed.store(index + 1, Ordering::Release);
+                if chunk.len() == chunk_length || index == count - 1 {
+                    progress.queued_chunks.fetch_add(1, Ordering::AcqRel);
+                    let chunk = std::mem::replace(&mut chunk, Vec::with_capacity(chunk_length));
+                    let chunk = ComputedChunk {
+                        index: index / chunk_length,
+                        points: chunk,
+                        partial: false,
+                    };
+                    if sender.send(chunk).is_err() {
+                        // The writer failed, its error is returned below.
+                        break;
+                    }
+                }
+            }
+            drop(sender);
+
+            entries.extend(writer.join().unwrap()?);
+            Ok::<_, anyhow::Error>(stopped)
+        })?;
+        if stopped {
+            if self.cancelled.load(Ordering::Relaxed) {
+                return Err(Cancelled(P::NAME).into());
+            }
+            return Err(anyhow!(
+                "{} generation interrupted by a signal, rerun with --spot-mode to resume",
+                P::NAME
+            ));
+        }
+        Ok(entries)
+    }
+
+    /// Generates the `P` set, cancelling the other curve if that fails.
+    fn run<P: Point>(&self, progress: &CurveProgress, config: &CurveConfig) -> Result<()> {
+        let result = self.generate::<P>(progress, config);
+        if result.is_err() {
+            self.cancelled.store(true, Ordering::Relaxed);
+        }
+        result
+    }
+
+    fn start_generate_g1(self: Pin<Arc<Self>>, config: CurveConfig) {
+        let generator = self.clone();
+        let mut handle = generator.g1_generator_handle.lock().unwrap();
+        *handle = Some(std::thread::spawn(move || {
+            let _guard = PanicGuard(&self);
+            self.run::<G1>(&self.g1_progress, &config)
+        }));
+    }
+
+    fn start_generate_g2(self: Pin<Arc<Self>>, config: CurveConfig) {
         let generator = self.clone();
         let mut handle = generator.g2_generator_handle.lock().unwrap();
         *handle = Some(std::thread::spawn(move || {
-            self.generate_g2(count, pattern.as_str(), chunk_length)
+            let _guard = PanicGuard(&self);
+            self.run::<G2>(&self.g2_progress, &config)
         }));
     }
 
-    fn join_all(&self) {
-        for handle in [
-            &self.g1_generator_handle,
-            &self.g2_generator_handle,
-            &self.reporter_handle,
+    /// Generates G2 on the calling thread, for `--no-g2-thread-when-small`. The result is returned
+    /// by `join_all` as if G2 had its own thread.
+    fn generate_g2_inline(&self, config: CurveConfig) {
+        let _guard = PanicGuard(self);
+        let result = self.run::<G2>(&self.g2_progress, &config);
+        *self.g2_inline_result.lock().unwrap() = Some(result);
+    }
+
+    /// Waits for all threads to finish and returns the first generator panic or error, if any.
+    /// Panics are returned in preference to errors, and errors in preference to the resulting
+    /// cancellation of the other curve.
+    fn join_all(&self) -> Result<()> {
+        let cancelled =
+            |result: &Result<()>| result.as_ref().is_err_and(|error| error.is::<Cancelled>());
+        let mut result = self
+            .g2_inline_result
+            .lock()
+            .unwrap()
+            .take()
+            .unwrap_or(Ok(()));
+        let mut panic = None;
+        for (name, handle) in [
+            (G1::NAME, &self.g1_generator_handle),
+            (G2::NAME, &self.g2_generator_handle),
         ] {
             let mut handle = handle.lock().unwrap();
             if let Some(handle) = handle.take() {
-                let _ = handle.join().unwrap();
+                match handle.join() {
+                    Ok(joined) => {
+                        if result.is_ok() || cancelled(&result) && joined.is_err() {
+                            result = joined;
+                        }
+                    }
+                    Err(payload) => {
+                        panic.get_or_insert_with(|| {
+                            anyhow!(
+                                "the {} generator thread panicked: {}",
+                                name,
+                                panic_message(payload.as_ref())
+                            )
+                        });
+                    }
+                }
             }
         }
+        if let Some(panic) = panic {
+            result = Err(panic);
+        }
+        self.done.store(true, Ordering::Release);
+        let mut handle = self.reporter_handle.lock().unwrap();
+        if let Some(handle) = handle.take() {
+            handle.join().unwrap()?;
+        }
+        result
     }
 }
 
 impl Drop for Generator {
     fn drop(&mut self) {
-        self.join_all();
+        let _ = self.join_all();
     }
 }
 
-fn main() -> Result<()> {
-    let args = Args::parse();
-    println!("G1 chunk length: {}", args.g1_chunk_length);
-    println!("G2 chunk length: {}", args.g2_chunk_length);
-    println!("G1 file pattern: {}", args.g1_pattern);
-    println!("G2 file pattern: {}", args.g2_pattern);
+fn finalize(args: FinalizeArgs) -> Result<()> {
+    if let Some(path) = &args.scratch_manifest {
+        let manifest = ScratchManifest::load(path.as_str())?.finalize()?;
+        manifest.save(args.manifest.as_str())?;
+        println!("{} finalized into {}", path, args.manifest);
+        return Ok(());
+    }
+    for path in &args.files {
+        let output = scratch::finalize(
+            path,
+            (args.format, args.format_version),
+            (args.g1_pattern.as_str(), args.g1_encoding),
+            (args.g2_pattern.as_str(), args.g2_encoding),
+        )?;
+        println!("{} finalized into {}", path, output);
+    }
+    Ok(())
+}
+
+fn vanishing_commitment(args: VanishingCommitmentArgs) -> Result<()> {
+    let manifest = Manifest::load(args.manifest.as_str())?;
+    let layout = &manifest
+        .curves
+        .get(G1::NAME)
+        .ok_or_else(|| anyhow!("the manifest has no {} entry", G1::NAME))?
+        .layout;
+    if layout.offset != 0 || layout.stride != 1 || layout.reverse {
+        return Err(anyhow!(
+            "vanishing commitments can only be computed from dense sets"
+        ));
+    }
+    let read = |index| reader::read_point::<G1>(layout, index);
+    let commitment = read(args.n)? - read(0)?;
+    println!("0x{}", hex::encode(commitment.to_bytes()));
+    Ok(())
+}
 
-    let generator = Generator::new();
+/// Warns that the tau of a repair couldn't be checked against the set, through the plain-text log
+/// on stdout.
+fn warn_unchecked_tau(repaired: &repair::Repaired) -> Result<()> {
+    if !repaired.tau_checked {
+        Output::new(1, None)?
+            .log("Warning: the set has no chunk left to check the provided tau against")?;
+    }
+    Ok(())
+}
 
-    generator.clone().start_generate_g1(
-        args.g1_count,
-        args.g1_pattern.clone(),
-        args.g1_chunk_length,
+fn repair_chunk(args: RepairChunkArgs) -> Result<()> {
+    let tau = args.tau.get_required()?;
+    let mut manifest = Manifest::load(args.manifest.as_str())?;
+    let repaired = match args.curve {
+        Curve::G1 => repair::repair_chunk::<G1>(&tau, &mut manifest, args.index)?,
+        Curve::G2 => repair::repair_chunk::<G2>(&tau, &mut manifest, args.index)?,
+    };
+    warn_unchecked_tau(&repaired)?;
+    manifest.save(args.manifest.as_str())?;
+    println!("{} repaired, {} updated", repaired.paths[0], args.manifest);
+    Ok(())
+}
+
+fn unknown_curve() -> validate::Problem {
+    validate::Problem {
+        chunk: None,
+        message: "unknown curve".to_string(),
+    }
+}
+
+/// Checks the structure of each curve of `manifest`, and the chunk hashes if `check_hashes`.
+/// Returns the problems found along with the names of their curves.
+fn validate_set(manifest: &Manifest, check_hashes: bool) -> Vec<(&str, validate::Problem)> {
+    let mut problems = vec![];
+    for (name, curve) in &manifest.curves {
+        let mut curve_problems = match name.as_str() {
+            G1::NAME => validate::check_indices::<G1>(curve),
+            G2::NAME => validate::check_indices::<G2>(curve),
+            _ => vec![unknown_curve()],
+        };
+        if check_hashes {
+            curve_problems.extend(validate::check_hashes(curve));
+        }
+        problems.extend(
+            curve_problems
+                .into_iter()
+                .map(|problem| (name.as_str(), problem)),
+        );
+    }
+    problems
+}
+
+/// Prints `problems` to stderr and fails if there are any.
+fn report_problems(problems: &[(&str, validate::Problem)]) -> Result<()> {
+    if problems.is_empty() {
+        return Ok(());
+    }
+    for (name, problem) in problems {
+        eprintln!("{}: {}", name, problem);
+    }
+    Err(anyhow!("{} problems found", problems.len()))
+}
+
+fn validate(args: ValidateArgs) -> Result<()> {
+    let manifest = Manifest::load(args.manifest.as_str())?;
+    let fingerprint = manifest.fingerprint();
+    let mut check_points = args.check_points;
+    if let Some(path) = &args.marker
+        && Marker::load(path)?.is_some_and(|marker| marker.fingerprint == fingerprint)
+    {
+        println!(
+            "{} records that the points of this set are valid, checking the hashes only",
+            path
+        );
+        check_points = false;
+    }
+    let mut problems = validate_set(&manifest, args.check_hashes || args.check_points);
+    if check_points {
+        for (name, curve) in &manifest.curves {
+            // Hashes were checked by `validate_set`, and unknown curves reported.
+            let curve_problems = match name.as_str() {
+                G1::NAME => validate::check_points::<G1>(curve),
+                G2::NAME => validate::check_points::<G2>(curve),
+                _ => vec![],
+            };
+            problems.extend(
+                curve_problems
+                    .into_iter()
+                    .map(|problem| (name.as_str(), problem)),
+            );
+        }
+    }
+    if let Some(path) = &args.failures {
+        let failures =
+            repair::format_failures(problems.iter().map(|(name, problem)| (*name, problem)));
+        std::fs::write(path, failures).with_context(|| format!("writing {}", path))?;
+    }
+    report_problems(&problems)?;
+    println!("The set described by {} is valid", args.manifest);
+    if check_points && let Some(path) = &args.marker {
+        Marker {
+            version: validate::MARKER_VERSION,
+            fingerprint,
+        }
+        .save(path)?;
+        println!("{} written", path);
+    }
+    Ok(())
+}
+
+fn repair(args: RepairArgs) -> Result<()> {
+    let path = args.repair_from_verify.as_str();
+    let list = std::fs::read_to_string(path).with_context(|| format!("reading {}", path))?;
+    let failures =
+        repair::parse_failures(list.as_str()).with_context(|| format!("parsing {}", path))?;
+    let tau = args.tau.get_required()?;
+    let mut manifest = Manifest::load(args.manifest.as_str())?;
+    for (name, indices) in &failures {
+        let repaired = match name.as_str() {
+            G1::NAME => repair::repair_chunks::<G1>(&tau, &mut manifest, indices)?,
+            G2::NAME => repair::repair_chunks::<G2>(&tau, &mut manifest, indices)?,
+            curve => return Err(anyhow!("unknown curve {}", curve)),
+        };
+        warn_unchecked_tau(&repaired)?;
+        for path in repaired.paths {
+            println!("{} repaired", path);
+        }
+        // Saved after each curve, so that the manifest matches the files if a later one fails.
+        manifest.save(args.manifest.as_str())?;
+    }
+    println!("{} updated, validating...", args.manifest);
+    report_problems(&validate_set(&manifest, true))?;
+    println!("The set described by {} is valid", args.manifest);
+    Ok(())
+}
+
+fn lookup_point<P: Point>(manifest: &Manifest, point: &str) -> Result<Option<u64>> {
+    let path = manifest
+        .curves
+        .get(P::NAME)
+        .and_then(|curve| curve.index_file.as_ref())
+        .ok_or_else(|| anyhow!("the manifest has no {} index", P::NAME))?;
+    let bytes = hex::decode(point.trim_start_matches("0x"))
+        .map_err(|error| anyhow!("invalid point hex: {}", error))?;
+    let point = P::decode(Encoding::Compressed, &bytes)?;
+    index::lookup::<P>(path.as_str(), &point)
+}
+
+fn lookup(args: LookupArgs) -> Result<()> {
+    let manifest = Manifest::load(args.manifest.as_str())?;
+    let index = match args.curve {
+        Curve::G1 => lookup_point::<G1>(&manifest, args.point.as_str())?,
+        Curve::G2 => lookup_point::<G2>(&manifest, args.point.as_str())?,
+    };
+    match index {
+        Some(index) => println!("{}", index),
+        None => return Err(anyhow!("the point is not in the set")),
+    }
+    Ok(())
+}
+
+fn equality_proof(args: EqualityProofArgs) -> Result<()> {
+    let nonce = hex::decode(args.nonce.trim_start_matches("0x"))
+        .map_err(|error| anyhow!("invalid nonce hex: {}", error))?;
+    if nonce.is_empty() {
+        return Err(anyhow!("the nonce must not be empty"));
+    }
+    let manifest = Manifest::load(args.manifest.as_str())?;
+    println!(
+        "{}",
+        equality::response(&manifest, nonce.as_slice(), args.samples)?
     );
+    Ok(())
+}
 
-    generator.clone().start_generate_g2(
+fn downsample(args: DownsampleArgs) -> Result<()> {
+    let source = Manifest::load(args.manifest.as_str())?;
+    let mut manifest = Manifest::default();
+    for (name, curve) in &source.curves {
+        let strided = match name.as_str() {
+            G1::NAME => downsample::downsample::<G1>(
+                curve,
+                args.stride,
+                args.offset,
+                args.g1_pattern.as_str(),
+            )?,
+            G2::NAME => downsample::downsample::<G2>(
+                curve,
+                args.stride,
+                args.offset,
+                args.g2_pattern.as_str(),
+            )?,
+            curve => return Err(anyhow!("unknown curve {}", curve)),
+        };
+        for chunk in &strided.chunks {
+            println!("{} written", chunk.path);
+        }
+        manifest.curves.insert(name.clone(), strided);
+    }
+    manifest.save(args.output.as_str())?;
+    println!("{} written", args.output);
+    Ok(())
+}
+
+fn relayout_curve<P: Point>(
+    curve: &mut CurveManifest,
+    pattern: Option<&String>,
+    chunk_length: Option<u64>,
+    keep: bool,
+) -> Result<()> {
+    let pattern = pattern.unwrap_or(&curve.layout.pattern).clone();
+    let chunk_length = chunk_length.unwrap_or(curve.layout.chunk_length);
+    match relayout::relayout::<P>(curve, pattern.as_str(), chunk_length, keep)? {
+        Relayout::Unchanged => println!("{} unchanged", P::NAME),
+        Relayout::Renamed => println!("{} chunks renamed to {}", P::NAME, pattern),
+        Relayout::Rechunked => println!(
+            "{} re-chunked into {} chunks of {} points",
+            P::NAME,
+            curve.chunks.len(),
+            chunk_length
+        ),
+    }
+    Ok(())
+}
+
+fn relayout(args: RelayoutArgs) -> Result<()> {
+    let mut manifest = Manifest::load(args.manifest.as_str())?;
+    let names: Vec<String> = manifest.curves.keys().cloned().collect();
+    for name in names {
+        let curve = manifest.curves.get_mut(&name).unwrap();
+        match name.as_str() {
+            G1::NAME => relayout_curve::<G1>(
+                curve,
+                args.g1_pattern.as_ref(),
+                args.g1_chunk_length,
+                args.keep,
+            )?,
+            G2::NAME => relayout_curve::<G2>(
+                curve,
+                args.g2_pattern.as_ref(),
+                args.g2_chunk_length,
+                args.keep,
+            )?,
+            curve => return Err(anyhow!("unknown curve {}", curve)),
+        }
+        // Saved after each curve, so that the manifest matches the files if a later one fails.
+        manifest.save(args.manifest.as_str())?;
+    }
+    println!("{} updated", args.manifest);
+    Ok(())
+}
+
+fn verify_signature(args: VerifySignatureArgs) -> Result<()> {
+    let key = signature::parse_public_key(args.pubkey.as_str())?;
+    let manifest = Manifest::load(args.manifest.as_str())?;
+    let path = args
+        .signatures
+        .unwrap_or_else(|| signature::path(args.manifest.as_str()));
+    let signatures = Signatures::load(path.as_str())?;
+    signatures.verify(&manifest, &key)?;
+    println!(
+        "{} is signed by {} (fingerprint {}, {} chunk signatures)",
+        args.manifest,
+        args.pubkey,
+        signatures.fingerprint,
+        signatures.chunks.len()
+    );
+    Ok(())
+}
+
+fn manifest_diff(args: ManifestDiffArgs) -> Result<()> {
+    let a = Manifest::load(args.a.as_str())?;
+    let b = Manifest::load(args.b.as_str())?;
+    let differences = manifest::diff((args.a.as_str(), &a), (args.b.as_str(), &b));
+    if !differences.is_empty() {
+        for difference in &differences {
+            println!("{}", difference);
+        }
+        return Err(anyhow!("{} differences found", differences.len()));
+    }
+    println!("{} and {} describe identical sets", args.a, args.b);
+    Ok(())
+}
+
+/// Assesses the set from its manifest: sets recorded by `verify-against-ceremony --record` as
+/// holding the tau of a multi-party ceremony are as sound as the ceremony, any other set is the
+/// output of a single run that knew tau.
+fn explain_security(args: ExplainSecurityArgs) -> Result<()> {
+    let manifest = Manifest::load(args.manifest.as_str())?;
+    let fingerprint = manifest.fingerprint();
+    let counts: Vec<String> = manifest
+        .curves
+        .iter()
+        .map(|(name, curve)| format!("{} {} points", curve.layout.count, name))
+        .collect();
+    println!("Set: {} ({})", args.manifest, counts.join(", "));
+    println!("Fingerprint: {}", fingerprint);
+    match &manifest.ceremony {
+        Some(ceremony) if ceremony.fingerprint == fingerprint => {
+            let contributions = ceremony
+                .contributions
+                .map_or("unknown".to_string(), |count| count.to_string());
+            println!(
+                "Contributions: {} (multi-party ceremony with tau·G1 = {})",
+                contributions, ceremony.tau_g1
+            );
+            println!(
+                "Toxic waste: tau combines the secrets of all contributions, nobody knows it \
+                 unless every contributor kept theirs"
+            );
+            println!(
+                "Assessment: the set is sound for production as long as a single contributor \
+                 destroyed their share, and verifiers trust the ceremony's tau·G1 and tau·G2. The \
+                 set was checked against them when the ceremony was recorded."
+            );
+        }
+        ceremony => {
+            if ceremony.is_some() {
+                println!(
+                    "Ceremony record: ignored, the set changed since it was checked against the \
+                     ceremony"
+                );
+            }
+            println!("Contributions: 1 (no multi-party computation transcript)");
+            println!(
+                "Toxic waste: tau was known to the machine and the operator of the generating run"
+            );
+            println!(
+                "Assessment: anyone who kept or recovered tau can forge proofs against this set. \
+                 It is fine for testing, and for deployments where the party that generated it is \
+                 trusted by all verifiers anyway. Other production uses need parameters from a \
+                 multi-party ceremony, which are sound as long as a single contributor destroyed \
+                 their share."
+            );
+        }
+    }
+    Ok(())
+}
+
+fn precompute_msm(args: PrecomputeMsmArgs) -> Result<()> {
+    let manifest = Manifest::load(args.manifest.as_str())?;
+    let layout = &manifest
+        .curves
+        .get(G1::NAME)
+        .ok_or_else(|| anyhow!("the manifest has no {} entry", G1::NAME))?
+        .layout;
+    let count = args.count.unwrap_or(layout.count);
+    pretable::write::<G1>(layout, count, args.window, args.output.as_str())?;
+    println!("{} written", args.output);
+    Ok(())
+}
+
+fn make_test_bundle(args: MakeTestBundleArgs) -> Result<()> {
+    let manifest = Manifest::load(args.manifest.as_str())?;
+    let bundle = Bundle::make(&manifest, args.num, args.degree)?;
+    bundle.save(args.output.as_str())?;
+    println!("{} written", args.output);
+    Ok(())
+}
+
+fn verify_bundle(args: VerifyBundleArgs) -> Result<()> {
+    let bundle = Bundle::load(args.bundle.as_str())?;
+    let problems = match &args.verifier_key {
+        Some(path) => bundle.verify_with(&VerifierKey::load(path)?),
+        None => bundle.verify()?,
+    };
+    if !problems.is_empty() {
+        for problem in &problems {
+            eprintln!("{}", problem);
+        }
+        return Err(anyhow!(
+            "{} of {} openings are invalid",
+            problems.len(),
+            bundle.openings.len()
+        ));
+    }
+    println!(
+        "All {} openings of {} are valid",
+        bundle.openings.len(),
+        args.bundle
+    );
+    Ok(())
+}
+
+fn bundle(args: BundleArgs) -> Result<()> {
+    let tau = match args.tau.get()? {
+        Some(tau) => tau,
+        None => tau::get_random_scalar().context("sampling a random tau")?,
+    };
+    ShiftedBundle::write(
+        &tau,
+        args.dir.as_str(),
+        args.g1_count,
         args.g2_count,
-        args.g2_pattern.clone(),
-        args.g2_chunk_length,
+        args.shift,
+        args.chunk_length,
+    )?;
+    println!("{} written", shifted::path(args.dir.as_str()));
+    Ok(())
+}
+
+fn verify_bundle_consistency(args: VerifyBundleConsistencyArgs) -> Result<()> {
+    let path = shifted::path(args.dir.as_str());
+    let problems = ShiftedBundle::load(path.as_str())?.check()?;
+    if !problems.is_empty() {
+        for problem in &problems {
+            eprintln!("{}", problem);
+        }
+        return Err(anyhow!("{} problems found", problems.len()));
+    }
+    println!("The sets of {} share its tau", path);
+    Ok(())
+}
+
+fn trim_verifier_key(args: TrimVerifierKeyArgs) -> Result<()> {
+    let manifest = Manifest::load(args.manifest.as_str())?;
+    VerifierKey::extract(&manifest)?.save(args.output.as_str())?;
+    println!("{} written", args.output);
+    Ok(())
+}
+
+fn check_degree(args: CheckDegreeArgs) -> Result<()> {
+    let manifest = Manifest::load(args.manifest.as_str())?;
+    let mut shortfalls = vec![];
+    for (name, needed) in [(G1::NAME, args.need.saturating_add(1)), (G2::NAME, 2)] {
+        match manifest.curves.get(name) {
+            None => shortfalls.push(format!(
+                "there is no {} set, {} powers are needed",
+                name, needed
+            )),
+            Some(curve) if !curve.layout.is_plain() => shortfalls.push(format!(
+                "the {} set doesn't hold consecutive powers of tau times the standard generator",
+                name
+            )),
+            Some(curve) if curve.layout.count < needed => shortfalls.push(format!(
+                "the {} set has {} powers, {} short of the {} needed",
+                name,
+                curve.layout.count,
+                needed - curve.layout.count,
+                needed
+            )),
+            Some(_) => {}
+        }
+    }
+    if !shortfalls.is_empty() {
+        for shortfall in &shortfalls {
+            eprintln!("{}", shortfall);
+        }
+        return Err(anyhow!(
+            "no, {} can't commit to polynomials of degree {}",
+            args.manifest,
+            args.need
+        ));
+    }
+    println!(
+        "Yes, {} can commit to polynomials of degree {}",
+        args.manifest, args.need
     );
+    Ok(())
+}
 
-    generator.join_all();
+/// Parses a hex-encoded compressed point published by a ceremony, returning it with its bytes.
+fn parse_ceremony_point<P: Point>(text: &str) -> Result<(P, Vec<u8>)> {
+    let bytes = hex::decode(text.trim().trim_start_matches("0x"))
+        .map_err(|error| anyhow!("invalid tau·{} hex: {}", P::NAME, error))?;
+    let point = P::decode(Encoding::Compressed, &bytes)
+        .with_context(|| format!("parsing tau·{}", P::NAME))?;
+    Ok((point, bytes))
+}
 
+fn verify_against_ceremony(args: VerifyAgainstCeremonyArgs) -> Result<()> {
+    let mut manifest = Manifest::load(args.manifest.as_str())?;
+    let (tau_g1, tau_g1_bytes) = parse_ceremony_point::<G1>(args.tau_g1.as_str())?;
+    let (tau_g2, tau_g2_bytes) = parse_ceremony_point::<G2>(args.tau_g2.as_str())?;
+    if !manifest.curves.contains_key(G1::NAME) {
+        return Err(anyhow!("the manifest has no {} entry", G1::NAME));
+    }
+    let mut problems = vec![];
+    for (name, curve) in &manifest.curves {
+        let curve_problems = match name.as_str() {
+            G1::NAME => ceremony::verify::<G1>(curve, &tau_g1, &tau_g2_bytes),
+            G2::NAME => ceremony::verify::<G2>(curve, &tau_g2, &tau_g1_bytes),
+            curve => return Err(anyhow!("unknown curve {}", curve)),
+        };
+        problems.extend(
+            curve_problems
+                .into_iter()
+                .map(|problem| (name.as_str(), problem)),
+        );
+    }
+    report_problems(&problems)?;
+    println!(
+        "The set described by {} holds the powers of the ceremony's tau",
+        args.manifest
+    );
+    if args.record {
+        manifest.ceremony = Some(CeremonyRecord {
+            tau_g1: hex::encode(tau_g1_bytes),
+            tau_g2: hex::encode(tau_g2_bytes),
+            contributions: args.contributions,
+            fingerprint: manifest.fingerprint(),
+        });
+        manifest.save(args.manifest.as_str())?;
+        println!("Ceremony recorded in {}", args.manifest);
+    }
     Ok(())
 }
+
+/// How long the rate of each curve is measured for by `check_deadline`.
+const BENCHMARK_DURATION: Duration = Duration::from_millis(500);
+
+/// Implements `--deadline`: estimates the compute time of the run and fails if it exceeds
+/// `deadline`, unless `--override-deadline` is given.
+fn check_deadline(args: &GenerateArgs, deadline: Duration, output: &Output) -> Result<()> {
+    output.log("Measuring the compute rate of this machine...")?;
+    let g1_rate = estimate::rate::<G1>(BENCHMARK_DURATION);
+    let g2_rate = estimate::rate::<G2>(BENCHMARK_DURATION);
+    let time = estimate::compute_time(
+        (args.g1_count as u64, g1_rate),
+        (args.g2_count as u64, g2_rate),
+        args.max_points_per_second,
+    );
+    output.log(format!(
+        "Estimated compute time: {} seconds at {:.0} G1 and {:.0} G2 pts/s, deadline in {} seconds",
+        time.as_secs(),
+        g1_rate,
+        g2_rate,
+        deadline.as_secs()
+    ))?;
+    if time <= deadline {
+        return Ok(());
+    }
+    if args.override_deadline {
+        output
+            .log("The run is likely to miss its deadline, starting anyway (--override-deadline)")?;
+        return Ok(());
+    }
+    Err(anyhow!(
+        "the run would take about {} seconds, more than the deadline of {} seconds; reduce the \
+         counts or pass --override-deadline to start anyway",
+        time.as_secs(),
+        deadline.as_secs()
+    ))
+}
+
+/// Appends the paths of the first files the run writes for the `P` set of `config` to `paths`.
+fn curve_outputs<P: Point>(config: CurveConfig, paths: &mut Vec<String>) -> Result<()> {
+    if config.layout.count == 0 {
+        return Ok(());
+    }
+    paths.push(config.layout.chunk_path::<P>(0)?);
+    paths.extend(config.index_path);
+    if let Some(pattern) = config.hiding_pattern {
+        let layout = Layout {
+            pattern,
+            ..config.layout
+        };
+        paths.push(layout.chunk_path::<P>(0)?);
+    }
+    Ok(())
+}
+
+/// Checks that all the directories the run writes to are writable, by creating and deleting a file
+/// in each, so that permission problems are reported before any work starts.
+fn check_writable(args: &GenerateArgs) -> Result<()> {
+    let mut paths = vec![];
+    curve_outputs::<G1>(args.g1_config(), &mut paths)?;
+    curve_outputs::<G2>(args.g2_config(), &mut paths)?;
+    if args.scratch {
+        paths.push(scratch::manifest_path(args.manifest.as_str()));
+    } else {
+        paths.push(args.manifest.clone());
+        paths.extend(
+            args.degree_views
+                .iter()
+                .map(|&log_count| view::path(args.manifest.as_str(), log_count)),
+        );
+        if args.sign_key.is_some() {
+            paths.push(signature::path(args.manifest.as_str()));
+        }
+    }
+    paths.extend(args.tau_encrypt.clone());
+    let mut directories: Vec<_> = paths
+        .iter()
+        .map(|path| match Path::new(path).parent() {
+            Some(parent) if !parent.as_os_str().is_empty() => parent.to_path_buf(),
+            _ => PathBuf::from("."),
+        })
+        .collect();
+    directories.sort();
+    directories.dedup();
+    for directory in directories {
+        let probe = directory.join(format!(".generate_params-probe-{}", std::process::id()));
+        std::fs::File::create(&probe)
+            .and_then(|_| std::fs::remove_file(&probe))
+            .map_err(|error| anyhow!("{} is not writable: {}", directory.display(), error))?;
+    }
+    Ok(())
+}
+
+fn generate(mut args: GenerateArgs) -> Result<()> {
+    let output = Arc::new(Output::new(args.progress_fd, args.log_fd)?);
+    if let Some(preset) = args.g2_from_g1 {
+        let (g2_count, reason) = preset.resolve(args.g1_count);
+        output.log(format!("G2 count: {} ({})", g2_count, reason))?;
+        args.g2_count = g2_count;
+    }
+    if let Some(total) = args.total_budget {
+        let (g1_count, g2_count) = budget::allocate(
+            args.budget_policy,
+            args.g1_count as u64,
+            args.g2_count as u64,
+            total,
+        );
+        output.log(format!(
+            "Total budget of {} points: generating {} G1 and {} G2 points",
+            total, g1_count, g2_count
+        ))?;
+        args.g1_count = g1_count as usize;
+        args.g2_count = g2_count as usize;
+    }
+    if let Some(extension) = &args.extension {
+        args.g1_pattern = pattern::with_extension(args.g1_pattern.as_str(), extension);
+        args.g2_pattern = pattern::with_extension(args.g2_pattern.as_str(), extension);
+        args.hiding_pattern = pattern::with_extension(args.hiding_pattern.as_str(), extension);
+    }
+    if args.resume || args.spot_mode {
+        // A curve without chunks on disk yet follows the other one.
+        let mut existing = resume::format_version::<G1>(&args.g1_config().layout)?;
+        if existing.is_none() {
+            existing = resume::format_version::<G2>(&args.g2_config().layout)?;
+        }
+        if let Some(version) = existing
+            && version != args.format_version
+        {
+            output.log(format!(
+                "Resuming in chunk format version {} like the existing chunks",
+                version
+            ))?;
+            args.format_version = version;
+        }
+    }
+    if let Some(block_size) = args.block_size {
+        if args.format != Format::Bincode {
+            return Err(anyhow!("--block-size requires the bincode format"));
+        }
+        args.g1_chunk_length =
+            layout::block_chunk_length::<G1>(block_size, args.format_version, args.g1_encoding)?
+                as usize;
+        args.g2_chunk_length =
+            layout::block_chunk_length::<G2>(block_size, args.format_version, args.g2_encoding)?
+                as usize;
+        output.log(format!(
+            "Chunk files padded to blocks of {} bytes",
+            block_size
+        ))?;
+    }
+    output.log(format!("G1 chunk length: {}", args.g1_chunk_length))?;
+    output.log(format!("G2 chunk length: {}", args.g2_chunk_length))?;
+    output.log(format!("G1 file pattern: {}", args.g1_pattern))?;
+    output.log(format!("G2 file pattern: {}", args.g2_pattern))?;
+    output.log(format!(
+        "Chunk format: {} version {}",
+        args.format.name(),
+        args.format_version
+    ))?;
+    output.log(format!("G1 encoding: {}", args.g1_encoding.name()))?;
+    output.log(format!("G2 encoding: {}", args.g2_encoding.name()))?;
+    if let Some(rate) = args.max_points_per_second {
+        output.log(format!("Compute rate capped at {} points per second", rate))?;
+    }
+    let g2_inline = args.no_g2_thread_when_small && args.g2_count <= SMALL_G2_COUNT;
+    if g2_inline {
+        output.log("Generating G2 on the main thread")?;
+    }
+    if args.even_powers || args.odd_powers {
+        let (offset, _) = args.powers();
+        output.log(format!(
+            "Generating only the {} powers of tau",
+            ["even", "odd"][offset as usize]
+        ))?;
+    }
+    if args.scratch {
+        output.log(format!(
+            "Writing scratch files, run `finalize --scratch-manifest {}` afterwards",
+            scratch::manifest_path(args.manifest.as_str())
+        ))?;
+    }
+
+    if let Some(points) = args.msm_bench
+        && points > args.g1_count
+    {
+        return Err(anyhow!(
+            "an MSM of {} points doesn't fit in the {} G1 points requested",
+            points,
+            args.g1_count
+        ));
+    }
+    if !args.degree_views.is_empty()
+        && (!args.g1_config().layout.is_plain() || !args.g2_config().layout.is_plain())
+    {
+        return Err(anyhow!(
+            "degree views are prefixes of the set, which only hold the powers up to a degree \
+             without --reverse, --even-powers, --odd-powers, or a custom generator"
+        ));
+    }
+    for &log_count in &args.degree_views {
+        if log_count >= u64::BITS || 1u64 << log_count > args.g1_count as u64 {
+            return Err(anyhow!(
+                "a view of 2^{} points doesn't fit in the {} G1 points requested",
+                log_count,
+                args.g1_count
+            ));
+        }
+    }
+
+    if args.dry_run {
+        let chunks = layout::plan_layout(&args.g1_config().layout, &args.g2_config().layout)?;
+        for chunk in &chunks {
+            println!(
+                "{} {} chunk {}: {} points from index {}, {} bytes",
+                chunk.path,
+                chunk.curve,
+                chunk.index,
+                chunk.count,
+                chunk.first_index,
+                chunk.byte_size
+            );
+        }
+        let total: u64 = chunks.iter().map(|chunk| chunk.byte_size).sum();
+        println!("{} files, {} bytes in total", chunks.len(), total);
+        return Ok(());
+    }
+
+    if let Some(deadline) = args.deadline {
+        check_deadline(&args, Duration::from_secs(deadline), &output)?;
+    }
+    check_writable(&args)?;
+    let signing_key = args
+        .sign_key
+        .as_deref()
+        .map(signature::load_signing_key)
+        .transpose()?;
+
+    let health = match &args.health_addr {
+        Some(address) => {
+            let health = Arc::new(Health::new(Duration::from_secs(args.health_stall_secs)));
+            let address = health.clone().serve(address.as_str())?;
+            output.log(format!("Serving /healthz on {}", address))?;
+            Some(health)
+        }
+        None => None,
+    };
+
+    let attempts = args.verify_and_retry.map_or(1, |retries| retries + 1);
+    for attempt in 1..=attempts {
+        let tau = args.tau()?;
+        if let Some(path) = &args.tau_encrypt {
+            SealedTau::seal(&tau, args.tau.passphrase()?.as_bytes())?.save(path)?;
+            output.log(format!("{} written", path))?;
+        }
+        let generator = Generator::new(
+            tau,
+            args.max_points_per_second.map(RateLimiter::new),
+            health.clone(),
+            output.clone(),
+        );
+        if args.spot_mode {
+            for signal in [signal_hook::consts::SIGTERM, signal_hook::consts::SIGINT] {
+                signal_hook::flag::register(signal, generator.stop.clone())?;
+            }
+        }
+
+        let mut g1_config = args.g1_config();
+        g1_config.inject_fault = args
+            .inject_fault_attempts
+            .is_some_and(|faulty| attempt <= faulty);
+        generator.clone().start_generate_g1(g1_config);
+        if g2_inline {
+            generator.generate_g2_inline(args.g2_config());
+        } else {
+            generator.clone().start_generate_g2(args.g2_config());
+        }
+
+        generator.join_all()?;
+
+        if args.verify_and_retry.is_some() {
+            let problems = generator.verify()?;
+            if !problems.is_empty() {
+                for problem in &problems {
+                    output.log(problem.as_str())?;
+                }
+                if attempt == attempts {
+                    return Err(anyhow!(
+                        "verification failed after {} attempts, the hardware may be faulty",
+                        attempts
+                    ));
+                }
+                discard(&generator.manifest.lock().unwrap())?;
+                output.log(format!(
+                    "Verification failed, output discarded, retrying with a fresh tau (attempt {} \
+                     of {})",
+                    attempt + 1,
+                    attempts
+                ))?;
+                continue;
+            }
+            output.log("Verification passed")?;
+        }
+
+        generator.finish(&args, signing_key.as_ref())?;
+        if let Some(points) = args.msm_bench {
+            generator.msm_bench(points)?;
+        }
+        break;
+    }
+    Ok(())
+}
+
+/// Removes the files of a discarded set: its chunks, hiding key chunks, and indices.
+fn discard(manifest: &Manifest) -> Result<()> {
+    for curve in manifest.curves.values() {
+        let hiding = curve.hiding.iter().flat_map(|hiding| &hiding.chunks);
+        let paths = curve.chunks.iter().chain(hiding).map(|chunk| &chunk.path);
+        for path in paths.chain(&curve.index_file) {
+            std::fs::remove_file(path).with_context(|| format!("removing {}", path))?;
+        }
+    }
+    Ok(())
+}
+
+fn main() -> Result<()> {
+    let args = Args::parse();
+    match args.command {
+        Some(Command::Finalize(args)) => finalize(args),
+        Some(Command::VanishingCommitment(args)) => vanishing_commitment(args),
+        Some(Command::RepairChunk(args)) => repair_chunk(args),
+        Some(Command::Validate(args)) => validate(args),
+        Some(Command::Repair(args)) => repair(args),
+        Some(Command::Lookup(args)) => lookup(args),
+        Some(Command::EqualityChallenge) => {
+            println!("{}", hex::encode(equality::new_nonce()?));
+            Ok(())
+        }
+        Some(Command::EqualityProof(args)) => equality_proof(args),
+        Some(Command::Downsample(args)) => downsample(args),
+        Some(Command::Relayout(args)) => relayout(args),
+        Some(Command::VerifySignature(args)) => verify_signature(args),
+        Some(Command::ManifestDiff(args)) => manifest_diff(args),
+        Some(Command::ExplainSecurity(args)) => explain_security(args),
+        Some(Command::PrecomputeMsm(args)) => precompute_msm(args),
+        Some(Command::VerifyAgainstCeremony(args)) => verify_against_ceremony(args),
+        Some(Command::MakeTestBundle(args)) => make_test_bundle(args),
+        Some(Command::VerifyBundle(args)) => verify_bundle(args),
+        Some(Command::CheckDegree(args)) => check_degree(args),
+        Some(Command::TrimVerifierKey(args)) => trim_verifier_key(args),
+        Some(Command::Bundle(args)) => bundle(args),
+        Some(Command::VerifyBundleConsistency(args)) => verify_bundle_consistency(args),
+        None => generate(args.generate),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn g2_presets_resolve_to_their_counts() {
+        let resolve = |mode: &str| mode.parse::<G2Preset>().unwrap().resolve(1024).0;
+        assert_eq!(resolve("minimal"), 2);
+        assert_eq!(resolve("symmetric"), 1024);
+        assert_eq!(resolve("custom:17"), 17);
+        for mode in ["", "custom", "custom:", "custom:x", "maximal"] {
+            assert!(mode.parse::<G2Preset>().is_err(), "{}", mode);
+        }
+    }
+}
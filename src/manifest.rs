@@ -0,0 +1,285 @@
+//! Set manifest, written as `params.json` next to the chunk files by default.
+//!
+//! The manifest records the layout of each curve (count, chunk length, file pattern, encoding)
+//! along with the path, point count, and BLAKE2b-256 hash of every chunk file, so that a set can
+//! be checked and maintained without knowing the options it was generated with.
+
+use crate::curve::Point;
+use crate::hiding::HidingManifest;
+use crate::layout::Layout;
+use anyhow::{Context, Result, anyhow};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+pub const VERSION: u32 = 1;
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ChunkEntry {
+    pub index: u64,
+    pub path: String,
+    pub count: u64,
+    /// Hex-encoded BLAKE2b-256 hash of the whole chunk file.
+    pub blake2b: String,
+    /// Where and when the chunk was generated, if recorded (see `--record-provenance`).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub provenance: Option<Provenance>,
+}
+
+/// Where and when a chunk was generated, for audits. It's kept in the manifest rather than in
+/// chunk headers, which are covered by chunk hashes and thus by the fingerprint, so that identical
+/// points yield identical files and fingerprints wherever and whenever they were generated.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Provenance {
+    /// Time the chunk was written at, in milliseconds since the Unix epoch (UTC).
+    pub time_ms: u64,
+    /// Host name of the generating machine, if known.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub host: Option<String>,
+    /// ID of the generating process.
+    pub pid: u32,
+}
+
+impl Provenance {
+    /// Describes the current process, now.
+    pub fn current() -> Self {
+        let host = std::fs::read_to_string("/proc/sys/kernel/hostname")
+            .ok()
+            .or_else(|| std::env::var("HOSTNAME").ok())
+            .map(|host| host.trim().to_string())
+            .filter(|host| !host.is_empty());
+        Self {
+            time_ms: SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map_or(0, |time| time.as_millis() as u64),
+            host,
+            pid: std::process::id(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CurveManifest {
+    #[serde(flatten)]
+    pub layout: Layout,
+    /// Chunk entries sorted by index.
+    pub chunks: Vec<ChunkEntry>,
+    /// Path of the sorted point index, if one was built (see `index`).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub index_file: Option<String>,
+    /// Hiding key, if one was generated (see `hiding`).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub hiding: Option<HidingManifest>,
+}
+
+/// Multi-party ceremony whose tau a set was found to hold by `verify-against-ceremony --record`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CeremonyRecord {
+    /// Hex-encoded compressed tau·G1 and tau·G2 published by the ceremony.
+    pub tau_g1: String,
+    pub tau_g2: String,
+    /// Number of contributions to the ceremony, if given.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub contributions: Option<u64>,
+    /// Fingerprint of the set when it was checked. The record only holds for that set.
+    pub fingerprint: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Manifest {
+    pub version: u32,
+    /// Curve manifests keyed by curve name ("G1", "G2").
+    pub curves: BTreeMap<String, CurveManifest>,
+    /// Ceremony the set was checked against, if recorded. Not part of the fingerprint.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ceremony: Option<CeremonyRecord>,
+}
+
+impl Default for Manifest {
+    fn default() -> Self {
+        Self {
+            version: VERSION,
+            curves: BTreeMap::new(),
+            ceremony: None,
+        }
+    }
+}
+
+/// Returns the hex-encoded BLAKE2b-256 hash of `data`.
+pub fn hash(data: &[u8]) -> String {
+    blake2b_simd::Params::new()
+        .hash_length(32)
+        .hash(data)
+        .to_hex()
+        .to_string()
+}
+
+impl Manifest {
+    pub fn load(path: &str) -> Result<Self> {
+        let json = std::fs::read_to_string(path).with_context(|| format!("reading {}", path))?;
+        let manifest: Self =
+            serde_json::from_str(json.as_str()).with_context(|| format!("parsing {}", path))?;
+        if manifest.version != VERSION {
+            return Err(anyhow!(
+                "{}: unsupported manifest version {}",
+                path,
+                manifest.version
+            ));
+        }
+        Ok(manifest)
+    }
+
+    pub fn save(&self, path: &str) -> Result<()> {
+        let mut json = serde_json::to_string_pretty(self)?;
+        json.push('\n');
+        std::fs::write(path, json).with_context(|| format!("writing {}", path))
+    }
+
+    /// Returns the hex-encoded fingerprint of the set: the BLAKE2b-256 hash of the name, layout,
+    /// and chunk hashes of each curve. The layout is hashed without its file pattern, and chunk
+    /// hashes cover the whole files, including headers, so the fingerprint identifies the points
+    /// and what they are the powers of wherever the files are.
+    pub fn fingerprint(&self) -> String {
+        let mut state = blake2b_simd::Params::new()
+            .hash_length(32)
+            .personal(b"GPFINGERPRINT")
+            .to_state();
+        for (name, curve) in &self.curves {
+            state.update(&(name.len() as u64).to_le_bytes());
+            state.update(name.as_bytes());
+            let layout = Layout {
+                pattern: String::new(),
+                ..curve.layout.clone()
+            };
+            let layout = serde_json::to_vec(&layout).expect("layouts serialize to JSON");
+            state.update(&(layout.len() as u64).to_le_bytes());
+            state.update(&layout);
+            state.update(&(curve.chunks.len() as u64).to_le_bytes());
+            for chunk in &curve.chunks {
+                state.update(chunk.blake2b.as_bytes());
+            }
+        }
+        state.finalize().to_hex().to_string()
+    }
+
+    pub fn curve_mut<P: Point>(&mut self) -> Result<&mut CurveManifest> {
+        self.curves
+            .get_mut(P::NAME)
+            .ok_or_else(|| anyhow!("the manifest has no {} entry", P::NAME))
+    }
+}
+
+/// Lists the differences between the chunks of two sets, using only their manifests: curves and
+/// chunks present in one of them only, and chunks with different point counts or hashes. Returns
+/// one message per difference, `a` and `b` naming the sets.
+pub fn diff(a: (&str, &Manifest), b: (&str, &Manifest)) -> Vec<String> {
+    let mut differences = vec![];
+    let names: BTreeSet<&String> = a.1.curves.keys().chain(b.1.curves.keys()).collect();
+    for name in names {
+        let (a_curve, b_curve) = match (a.1.curves.get(name), b.1.curves.get(name)) {
+            (Some(a_curve), Some(b_curve)) => (a_curve, b_curve),
+            (_, None) => {
+                differences.push(format!("{}: only in {}", name, a.0));
+                continue;
+            }
+            (None, _) => {
+                differences.push(format!("{}: only in {}", name, b.0));
+                continue;
+            }
+        };
+        let a_chunks: BTreeMap<u64, &ChunkEntry> = a_curve
+            .chunks
+            .iter()
+            .map(|chunk| (chunk.index, chunk))
+            .collect();
+        let b_chunks: BTreeMap<u64, &ChunkEntry> = b_curve
+            .chunks
+            .iter()
+            .map(|chunk| (chunk.index, chunk))
+            .collect();
+        let indices: BTreeSet<u64> = a_chunks.keys().chain(b_chunks.keys()).copied().collect();
+        for index in indices {
+            match (a_chunks.get(&index), b_chunks.get(&index)) {
+                (Some(a_chunk), Some(b_chunk)) if a_chunk.count != b_chunk.count => differences
+                    .push(format!(
+                        "{} chunk {}: {} points in {}, {} in {}",
+                        name, index, a_chunk.count, a.0, b_chunk.count, b.0
+                    )),
+                (Some(a_chunk), Some(b_chunk)) if a_chunk.blake2b != b_chunk.blake2b => differences
+                    .push(format!(
+                        "{} chunk {}: hash {} in {}, {} in {}",
+                        name, index, a_chunk.blake2b, a.0, b_chunk.blake2b, b.0
+                    )),
+                (Some(_), Some(_)) => {}
+                (_, None) => differences.push(format!("{} chunk {}: only in {}", name, index, a.0)),
+                (None, _) => differences.push(format!("{} chunk {}: only in {}", name, index, b.0)),
+            }
+        }
+    }
+    differences
+}
+
+impl CurveManifest {
+    /// Replaces the entry of the same index, or inserts it in order.
+    pub fn set_chunk(&mut self, entry: ChunkEntry) {
+        match self
+            .chunks
+            .binary_search_by_key(&entry.index, |chunk| chunk.index)
+        {
+            Ok(position) => self.chunks[position] = entry,
+            Err(position) => self.chunks.insert(position, entry),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn manifest(hashes: &[&str]) -> Manifest {
+        let chunks = hashes
+            .iter()
+            .enumerate()
+            .map(|(index, hash)| ChunkEntry {
+                index: index as u64,
+                path: format!("g1_{}.bin", index),
+                count: 4,
+                blake2b: hash.to_string(),
+                provenance: None,
+            })
+            .collect();
+        let curve = CurveManifest {
+            layout: Layout::dense(std::path::Path::new("."), 4 * hashes.len() as u64, 4),
+            chunks,
+            index_file: None,
+            hiding: None,
+        };
+        let mut manifest = Manifest::default();
+        manifest.curves.insert("G1".to_string(), curve);
+        manifest
+    }
+
+    #[test]
+    fn manifests_differing_in_one_chunk_are_diffed() {
+        let a = manifest(&["aa", "bb", "cc"]);
+        assert_eq!(diff(("a", &a), ("b", &a)), Vec::<String>::new());
+
+        let b = manifest(&["aa", "bd", "cc"]);
+        assert_eq!(
+            diff(("a", &a), ("b", &b)),
+            ["G1 chunk 1: hash bb in a, bd in b"]
+        );
+
+        let mut b = manifest(&["aa", "bb"]);
+        b.curves.get_mut("G1").unwrap().chunks[0].count = 3;
+        b.curves.insert("G2".to_string(), b.curves["G1"].clone());
+        assert_eq!(
+            diff(("a", &a), ("b", &b)),
+            [
+                "G1 chunk 0: 4 points in a, 3 in b",
+                "G1 chunk 2: only in a",
+                "G2: only in b",
+            ]
+        );
+    }
+}
@@ -0,0 +1,102 @@
+//! Benchmark of a polynomial commitment over the powers of a set, to help size a set for a prover
+//! (see `--msm-bench`).
+
+use crate::chunk;
+use crate::curve::Point;
+use crate::layout::Layout;
+use crate::powers;
+use crate::tau;
+use anyhow::{Result, anyhow};
+use blstrs::Scalar;
+use ff::Field;
+use rand_chacha::ChaCha20Rng;
+use rand_chacha::rand_core::SeedableRng;
+use std::time::{Duration, Instant};
+
+#[derive(Debug, Clone, Copy)]
+pub struct Bench {
+    /// Number of points of the MSM.
+    pub points: usize,
+    pub elapsed: Duration,
+    /// Whether the commitment matches the one computed from tau.
+    pub correct: bool,
+}
+
+impl Bench {
+    pub fn points_per_second(&self) -> f64 {
+        self.points as f64 / self.elapsed.as_secs_f64()
+    }
+}
+
+/// Commits to a random polynomial with `points` coefficients with the first `points` points of the
+/// `P` set described by `layout`, timing the MSM, and checks the commitment against `tau`. The
+/// points are read before the timer starts.
+pub fn bench<P: Point>(layout: &Layout, tau: &Scalar, points: usize) -> Result<Bench> {
+    if points as u64 > layout.count {
+        return Err(anyhow!(
+            "can't benchmark an MSM of {} points, the {} set has {}",
+            points,
+            P::NAME,
+            layout.count
+        ));
+    }
+    let mut bases = Vec::with_capacity(points);
+    for index in 0..layout.chunk_count() {
+        if bases.len() == points {
+            break;
+        }
+        let chunk = chunk::read::<P>(layout.chunk_path::<P>(index)?.as_str())?;
+        bases.extend(chunk.into_iter().take(points - bases.len()));
+    }
+    let mut rng = ChaCha20Rng::from_seed(tau::random_seed()?);
+    let scalars: Vec<Scalar> = (0..points).map(|_| Scalar::random(&mut rng)).collect();
+
+    let start = Instant::now();
+    let commitment = P::multi_exp(&bases, &scalars);
+    let elapsed = start.elapsed();
+
+    let step = layout.step(tau);
+    let mut power = powers::power(tau, layout.power(0));
+    let mut evaluation = Scalar::ZERO;
+    for scalar in &scalars {
+        evaluation += scalar * power;
+        power *= step;
+    }
+    Ok(Bench {
+        points,
+        elapsed,
+        correct: commitment == layout.base::<P>()? * evaluation,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::curve::G1;
+
+    #[test]
+    fn benchmark_commitments_are_checked_against_tau() {
+        let dir = tempfile::tempdir().unwrap();
+        let tau = Scalar::from(7u64);
+        let layout = Layout::dense(dir.path(), 10, 4);
+        layout.write_set::<G1>(&tau);
+        let result = bench::<G1>(&layout, &tau, 6).unwrap();
+        assert_eq!(result.points, 6);
+        assert!(result.correct);
+        assert!(
+            !bench::<G1>(&layout, &Scalar::from(8u64), 6)
+                .unwrap()
+                .correct
+        );
+        assert!(bench::<G1>(&layout, &tau, 11).is_err());
+
+        let dir = tempfile::tempdir().unwrap();
+        let odd = Layout {
+            offset: 1,
+            stride: 2,
+            ..Layout::dense(dir.path(), 10, 4)
+        };
+        odd.write_set::<G1>(&tau);
+        assert!(bench::<G1>(&odd, &tau, 10).unwrap().correct);
+    }
+}
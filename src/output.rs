@@ -0,0 +1,179 @@
+//! Routing of the human-readable progress line and of log messages.
+//!
+//! By default both go to stdout as plain text. Either can be redirected to another file
+//! descriptor, in which case log messages become JSON lines so that a supervisor can parse them
+//! while a human watches the progress line on a terminal. Named pipes can be used by redirecting
+//! the descriptor in the shell, e.g. `3>/path/to/fifo`.
+
+use anyhow::{Context, Result, anyhow};
+use serde::Serialize;
+use std::fs::File;
+use std::io::{self, Write};
+use std::sync::Mutex;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+#[derive(Debug)]
+enum Stream {
+    Stdout,
+    Stderr,
+    File(File),
+}
+
+impl Stream {
+    fn open(fd: i32) -> Result<Self> {
+        match fd {
+            1 => Ok(Self::Stdout),
+            2 => Ok(Self::Stderr),
+            fd => Self::from_fd(fd),
+        }
+    }
+
+    #[cfg(unix)]
+    fn from_fd(fd: i32) -> Result<Self> {
+        use std::os::fd::FromRawFd;
+        if fd < 0 {
+            return Err(anyhow!("invalid file descriptor: {}", fd));
+        }
+        if fd == 0 {
+            return Err(anyhow!(
+                "file descriptor 0 is stdin, it can't be written to"
+            ));
+        }
+        // SAFETY: F_GETFL only reads the flags of the descriptor, failing if it isn't open.
+        let flags = unsafe { libc::fcntl(fd, libc::F_GETFL) };
+        if flags == -1 {
+            return Err(anyhow!(
+                "file descriptor {} is not open, redirect it first, e.g. with `{}>path`",
+                fd,
+                fd
+            ));
+        }
+        if flags & libc::O_ACCMODE == libc::O_RDONLY {
+            return Err(anyhow!("file descriptor {} is not open for writing", fd));
+        }
+        // SAFETY: the descriptor is open, so files opened later can't be given its number, and it
+        // was handed to the process for this output (typically through a shell redirection), so
+        // nothing else in the process owns it.
+        Ok(Self::File(unsafe { File::from_raw_fd(fd) }))
+    }
+
+    #[cfg(not(unix))]
+    fn from_fd(fd: i32) -> Result<Self> {
+        Err(anyhow!(
+            "file descriptor {} is not supported on this platform",
+            fd
+        ))
+    }
+
+    fn write_flush(&mut self, bytes: &[u8]) -> io::Result<()> {
+        match self {
+            Self::Stdout => {
+                let mut stdout = io::stdout();
+                stdout.write_all(bytes).and_then(|_| stdout.flush())
+            }
+            Self::Stderr => io::stderr().write_all(bytes),
+            Self::File(file) => file.write_all(bytes),
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+struct LogRecord<'a> {
+    time_ms: u128,
+    message: &'a str,
+}
+
+#[derive(Debug)]
+struct Streams {
+    progress: Stream,
+    /// The log stream, or `None` if the log shares the progress stream.
+    log: Option<Stream>,
+    /// Whether the last thing written to the progress stream is an unterminated progress line.
+    progress_pending: bool,
+}
+
+#[derive(Debug)]
+pub struct Output {
+    /// Whether log messages are written as JSON lines rather than plain text.
+    json: bool,
+    streams: Mutex<Streams>,
+}
+
+impl Output {
+    /// Creates the output streams. Passing `log_fd` switches the log to JSON lines on that
+    /// descriptor, otherwise it's plain text on stdout.
+    pub fn new(progress_fd: i32, log_fd: Option<i32>) -> Result<Self> {
+        if log_fd == Some(progress_fd) {
+            return Err(anyhow!(
+                "the log and the progress line must use different file descriptors"
+            ));
+        }
+        let log = match log_fd.unwrap_or(1) {
+            fd if fd == progress_fd => None,
+            fd => Some(Stream::open(fd)?),
+        };
+        Ok(Self {
+            json: log_fd.is_some(),
+            streams: Mutex::new(Streams {
+                progress: Stream::open(progress_fd)?,
+                log,
+                progress_pending: false,
+            }),
+        })
+    }
+
+    /// Replaces the current progress line.
+    pub fn progress(&self, line: impl AsRef<str>) -> Result<()> {
+        let mut streams = self.streams.lock().unwrap();
+        streams
+            .progress
+            .write_flush(format!("\r{}", line.as_ref()).as_bytes())
+            .context("writing the progress line")?;
+        streams.progress_pending = true;
+        Ok(())
+    }
+
+    /// Terminates the current progress line, if any.
+    pub fn finish_progress(&self) -> Result<()> {
+        let mut streams = self.streams.lock().unwrap();
+        if streams.progress_pending {
+            streams
+                .progress
+                .write_flush(b"\n")
+                .context("writing the progress line")?;
+            streams.progress_pending = false;
+        }
+        Ok(())
+    }
+
+    /// Writes a log message. Fails if the log can't be written to, e.g. if the supervisor reading
+    /// it exited.
+    pub fn log(&self, message: impl AsRef<str>) -> Result<()> {
+        let message = message.as_ref();
+        let mut line = if self.json {
+            let record = LogRecord {
+                time_ms: SystemTime::now()
+                    .duration_since(UNIX_EPOCH)
+                    .unwrap()
+                    .as_millis(),
+                message,
+            };
+            serde_json::to_string(&record).unwrap()
+        } else {
+            message.to_string()
+        };
+        line.push('\n');
+        let mut streams = self.streams.lock().unwrap();
+        let result = match &mut streams.log {
+            Some(log) => log.write_flush(line.as_bytes()),
+            None => {
+                if streams.progress_pending {
+                    line.insert(0, '\n');
+                    streams.progress_pending = false;
+                }
+                streams.progress.write_flush(line.as_bytes())
+            }
+        };
+        result.context("writing the log")
+    }
+}
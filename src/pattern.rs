@@ -0,0 +1,143 @@
+//! File name patterns.
+//!
+//! Patterns may contain the following placeholders:
+//!
+//! - `{curve}`: lowercase curve name, e.g. `g1`;
+//! - `{index}`: chunk index;
+//! - `{start}`: global index of the first point of the chunk;
+//! - `{count}`: number of points in the chunk;
+//! - `{encoding}`: point encoding, e.g. `compressed`.
+//!
+//! `{}` is an alias of `{index}`, kept for backward compatibility. Numeric placeholders accept a
+//! minimum width, zero-padded if it starts with 0: `{start:07}` renders 42 as `0000042`, and `{:4}`
+//! renders chunk 3 as `   3`. `{{` and `}}` stand for literal braces.
+
+use anyhow::{Result, anyhow};
+
+/// Values substituted for the placeholders of a pattern.
+#[derive(Debug, Clone, Copy)]
+pub struct Fields<'a> {
+    pub curve: &'a str,
+    pub index: u64,
+    pub start: u64,
+    pub count: u64,
+    pub encoding: &'a str,
+}
+
+fn render_placeholder(placeholder: &str, fields: &Fields) -> Result<String> {
+    let (name, spec) = placeholder.split_once(':').unwrap_or((placeholder, ""));
+    let number = match name {
+        "" | "index" => fields.index,
+        "start" => fields.start,
+        "count" => fields.count,
+        "curve" | "encoding" if !spec.is_empty() => {
+            return Err(anyhow!("{{{}}} doesn't accept a width", name));
+        }
+        "curve" => return Ok(fields.curve.to_lowercase()),
+        "encoding" => return Ok(fields.encoding.to_string()),
+        _ => return Err(anyhow!("unknown placeholder {{{}}}", placeholder)),
+    };
+    if spec.is_empty() {
+        return Ok(number.to_string());
+    }
+    let width: usize = spec
+        .parse()
+        .map_err(|_| anyhow!("invalid width in {{{}}}", placeholder))?;
+    Ok(if spec.starts_with('0') {
+        format!("{:0width$}", number)
+    } else {
+        format!("{:width$}", number)
+    })
+}
+
+/// Renders `pattern` with the given field values.
+pub fn render(pattern: &str, fields: &Fields) -> Result<String> {
+    let mut output = String::with_capacity(pattern.len());
+    let mut rest = pattern;
+    while let Some(position) = rest.find(['{', '}']) {
+        output.push_str(&rest[..position]);
+        let brace = &rest[position..];
+        if brace.starts_with("{{") || brace.starts_with("}}") {
+            output.push_str(&brace[..1]);
+            rest = &brace[2..];
+        } else if let Some(placeholder) = brace.strip_prefix('{') {
+            let end = placeholder
+                .find('}')
+                .ok_or_else(|| anyhow!("unterminated placeholder in pattern {:?}", pattern))?;
+            output.push_str(render_placeholder(&placeholder[..end], fields)?.as_str());
+            rest = &placeholder[end + 1..];
+        } else {
+            return Err(anyhow!("unmatched `}}` in pattern {:?}", pattern));
+        }
+    }
+    output.push_str(rest);
+    Ok(output)
+}
+
+/// Replaces the extension of the file names rendered by `pattern`, i.e. what follows the last `.`
+/// of its last path component if no placeholder comes after it, with `extension`, or removes it if
+/// `extension` is empty. A leading `.` in `extension` is ignored, so that `srs` and `.srs` both turn
+/// `g1_{}.bin` into `g1_{}.srs`, never `g1_{}.bin.srs`.
+pub fn with_extension(pattern: &str, extension: &str) -> String {
+    let name_start = pattern.rfind('/').map_or(0, |position| position + 1);
+    let name = &pattern[name_start..];
+    let stem_end = match name.rfind('.') {
+        Some(dot) if dot > 0 && !name[dot..].contains(['{', '}']) => name_start + dot,
+        _ => pattern.len(),
+    };
+    let extension = extension.trim_start_matches('.');
+    if extension.is_empty() {
+        pattern[..stem_end].to_string()
+    } else {
+        format!("{}.{}", &pattern[..stem_end], extension)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fields(index: u64) -> Fields<'static> {
+        Fields {
+            curve: "G1",
+            index,
+            start: index * 65536,
+            count: 65536,
+            encoding: "compressed",
+        }
+    }
+
+    #[test]
+    fn placeholders_are_rendered() {
+        let pattern = "srs_{curve}_enc-{encoding}_start-{start:07}_{count}_{index:3}.bin";
+        assert_eq!(
+            render(pattern, &fields(2)).unwrap(),
+            "srs_g1_enc-compressed_start-0131072_65536_  2.bin"
+        );
+        assert_eq!(render("g1_{}.bin", &fields(2)).unwrap(), "g1_2.bin");
+        assert_eq!(render("{{{index}}}", &fields(2)).unwrap(), "{2}");
+    }
+
+    #[test]
+    fn invalid_placeholders_are_rejected() {
+        for pattern in ["{size}", "{curve:3}", "{start:x}", "g1_{", "g1_}"] {
+            assert!(render(pattern, &fields(0)).is_err(), "{}", pattern);
+        }
+    }
+
+    #[test]
+    fn extensions_replace_the_last_one() {
+        for (pattern, extension, expected) in [
+            ("g1_{}.bin", "srs", "g1_{}.srs"),
+            ("g1_{}.bin", ".srs", "g1_{}.srs"),
+            ("g1_{}.bin", "", "g1_{}"),
+            ("g1_{}", "points", "g1_{}.points"),
+            ("g1_{}.tar.bin", "srs", "g1_{}.tar.srs"),
+            ("set.v2/g1_{}", "srs", "set.v2/g1_{}.srs"),
+            ("g1_{curve}.{index}", "srs", "g1_{curve}.{index}.srs"),
+            (".hidden_{}", "srs", ".hidden_{}.srs"),
+        ] {
+            assert_eq!(with_extension(pattern, extension), expected, "{}", pattern);
+        }
+    }
+}
@@ -0,0 +1,108 @@
+use crate::curve::{G1, Point};
+use blstrs::Scalar;
+use ff::Field;
+
+/// Returns tau^exponent.
+pub fn power(tau: &Scalar, exponent: u64) -> Scalar {
+    tau.pow_vartime([exponent])
+}
+
+/// Computes the `count` consecutive powers tau^first·G, ..., tau^(first+count-1)·G, jumping
+/// straight to the first one by fast exponentiation.
+pub fn compute<P: Point>(tau: &Scalar, first: u64, count: usize) -> Vec<P> {
+    compute_from(P::generator(), tau, first, count)
+}
+
+/// Computes the `count` powers tau^first·base, tau^(first+stride)·base, tau^(first+2·stride)·base,
+/// ...
+pub fn compute_strided<P: Point>(
+    base: P,
+    tau: &Scalar,
+    first: u64,
+    stride: u64,
+    count: usize,
+) -> Vec<P> {
+    let step = power(tau, stride);
+    let mut points = Vec::with_capacity(count);
+    let mut g = base * power(tau, first);
+    for _ in 0..count {
+        points.push(g);
+        g *= step;
+    }
+    points
+}
+
+/// Like `compute`, but for powers of tau times `base` instead of the standard generator.
+pub fn compute_from<P: Point>(base: P, tau: &Scalar, first: u64, count: usize) -> Vec<P> {
+    let mut points = Vec::with_capacity(count);
+    let mut g = base * power(tau, first);
+    for _ in 0..count {
+        points.push(g);
+        g *= tau;
+    }
+    points
+}
+
+/// How `scalars_to_points` turns a sequence of scalars into points.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ScalarMode {
+    /// Maps each scalar s_i to s_i·G independently.
+    Map,
+    /// Multiplies the previous point by each scalar in turn, yielding (s_0·...·s_i)·G. Feeding
+    /// 1, tau, tau, ... yields the consecutive powers of tau.
+    Accumulate,
+}
+
+/// Turns each scalar of `scalars` into a point of `P` according to `mode`, lazily.
+pub fn scalars_to_points<P: Point>(
+    scalars: impl Iterator<Item = Scalar>,
+    mode: ScalarMode,
+) -> impl Iterator<Item = P> {
+    let mut g = P::generator();
+    scalars.map(move |scalar| match mode {
+        ScalarMode::Map => P::generator() * scalar,
+        ScalarMode::Accumulate => {
+            g *= scalar;
+            g
+        }
+    })
+}
+
+/// `scalars_to_points` for G1, for arbitrary commitment key layouts.
+pub fn scalars_to_g1(
+    scalars: impl Iterator<Item = Scalar>,
+    mode: ScalarMode,
+) -> impl Iterator<Item = G1> {
+    scalars_to_points(scalars, mode)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use group::Group;
+
+    #[test]
+    fn scalars_are_mapped_to_points() {
+        let scalars = [3u64, 5, 0].map(Scalar::from);
+        let points: Vec<_> = scalars_to_g1(scalars.into_iter(), ScalarMode::Map).collect();
+        assert_eq!(
+            points,
+            [
+                G1::generator() * scalars[0],
+                G1::generator() * scalars[1],
+                G1::identity()
+            ]
+        );
+    }
+
+    #[test]
+    fn scalars_are_accumulated() {
+        let tau = Scalar::from(7u64);
+        let scalars = std::iter::once(Scalar::ONE).chain(std::iter::repeat_n(tau, 9));
+        let points: Vec<_> = scalars_to_g1(scalars, ScalarMode::Accumulate).collect();
+        assert_eq!(points, compute::<G1>(&tau, 0, 10));
+        let scalars = [2u64, 3, 5].map(Scalar::from);
+        let points: Vec<_> = scalars_to_g1(scalars.into_iter(), ScalarMode::Accumulate).collect();
+        assert_eq!(points[2], G1::generator() * Scalar::from(30u64));
+    }
+}
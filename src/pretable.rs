@@ -0,0 +1,243 @@
+//! Precomputed tables for repeated fixed-base MSMs over the powers of a set.
+//!
+//! Provers committing to many polynomials with the same bases can trade memory for the doublings
+//! of Pippenger's algorithm. With scalars split into windows of `w` bits, the table of a base P
+//! holds 2^(j·w)·P for every window j, so that a whole MSM is a single round of bucket
+//! accumulation over all the (base, window) pairs followed by one bucket sum, instead of one round
+//! per window with `w` doublings in between.
+//!
+//! A table file is a `HEADER_SIZE`-byte header followed by `count · windows` uncompressed affine
+//! points, base-major: the entry for base i and window j starts at byte HEADER_SIZE + (i·windows +
+//! j)·point_size, so that provers can mmap the file and index it directly. The header is the magic
+//! bytes "GPMSMTBL", then the window width `w`, the number of windows ceil(255 / w), and the number
+//! of bases, as little-endian u32, u32, and u64. Base i is point i of the set, whatever power of tau
+//! the set's offset and stride make it.
+//!
+//! Tables are ceil(255 / w) times as large as the uncompressed set, e.g. 3 GiB for 2^20 G1 powers
+//! with 8-bit windows.
+
+use crate::chunk;
+use crate::curve::{Encoding, Point};
+use crate::layout::Layout;
+use anyhow::{Context, Result, anyhow};
+use blstrs::Scalar;
+use ff::PrimeField;
+use group::UncompressedEncoding;
+use group::prime::PrimeCurveAffine;
+use std::fs::File;
+use std::io::{BufReader, BufWriter, Read, Write};
+
+const MAGIC: [u8; 8] = *b"GPMSMTBL";
+
+pub const HEADER_SIZE: usize = 24;
+
+/// Largest supported window width. Wider windows need 2^w buckets per MSM.
+pub const MAX_WINDOW: u32 = 16;
+
+/// Bit length of the scalars.
+const SCALAR_BITS: u32 = Scalar::NUM_BITS;
+
+/// Returns the number of `window`-bit windows covering a scalar.
+pub fn windows(window: u32) -> u32 {
+    SCALAR_BITS.div_ceil(window)
+}
+
+fn check_window(window: u32) -> Result<()> {
+    if !(1..=MAX_WINDOW).contains(&window) {
+        return Err(anyhow!(
+            "the window must be between 1 and {} bits, got {}",
+            MAX_WINDOW,
+            window
+        ));
+    }
+    Ok(())
+}
+
+/// Writes the table with `window`-bit windows of the first `count` points of the `P` set described
+/// by `layout` to `path`.
+pub fn write<P: Point>(layout: &Layout, count: u64, window: u32, path: &str) -> Result<()> {
+    check_window(window)?;
+    if count > layout.count {
+        return Err(anyhow!(
+            "can't precompute {} bases, the {} set has {}",
+            count,
+            P::NAME,
+            layout.count
+        ));
+    }
+    let windows = windows(window) as usize;
+    let file = File::create(path).with_context(|| format!("creating {}", path))?;
+    let mut writer = BufWriter::new(file);
+    let mut header = Vec::with_capacity(HEADER_SIZE);
+    header.extend_from_slice(&MAGIC);
+    header.extend_from_slice(&window.to_le_bytes());
+    header.extend_from_slice(&(windows as u32).to_le_bytes());
+    header.extend_from_slice(&count.to_le_bytes());
+    writer
+        .write_all(&header)
+        .with_context(|| format!("writing {}", path))?;
+    let mut written = 0;
+    for index in 0..layout.chunk_count() {
+        if written == count {
+            break;
+        }
+        let points = chunk::read::<P>(layout.chunk_path::<P>(index)?.as_str())?;
+        for point in points.iter().take((count - written) as usize) {
+            let mut shifted = Vec::with_capacity(windows);
+            let mut base = *point;
+            for _ in 0..windows {
+                shifted.push(base);
+                for _ in 0..window {
+                    base = base.double();
+                }
+            }
+            let mut affine = vec![P::Affine::identity(); windows];
+            P::batch_normalize(&shifted, &mut affine);
+            for point in &affine {
+                writer
+                    .write_all(point.to_uncompressed().as_ref())
+                    .with_context(|| format!("writing {}", path))?;
+            }
+            written += 1;
+        }
+    }
+    writer.flush().with_context(|| format!("writing {}", path))
+}
+
+/// A table loaded in memory.
+pub struct Table<P: Point> {
+    window: u32,
+    windows: usize,
+    points: Vec<P::Affine>,
+}
+
+impl<P: Point> Table<P> {
+    /// Reads the table at `path`, checking every point.
+    pub fn load(path: &str) -> Result<Self> {
+        let file = File::open(path).with_context(|| format!("opening {}", path))?;
+        let mut reader = BufReader::new(file);
+        let mut header = [0u8; HEADER_SIZE];
+        reader
+            .read_exact(&mut header)
+            .with_context(|| format!("reading {}", path))?;
+        if header[..8] != MAGIC {
+            return Err(anyhow!("{} is not an MSM table", path));
+        }
+        let window = u32::from_le_bytes(header[8..12].try_into().unwrap());
+        let windows = u32::from_le_bytes(header[12..16].try_into().unwrap());
+        let count = u64::from_le_bytes(header[16..24].try_into().unwrap());
+        check_window(window).with_context(|| format!("reading {}", path))?;
+        if windows != self::windows(window) {
+            return Err(anyhow!(
+                "{} has {} windows of {} bits, expected {}",
+                path,
+                windows,
+                window,
+                self::windows(window)
+            ));
+        }
+        let point_size = P::encoded_size(Encoding::Uncompressed);
+        let mut bytes = vec![0u8; point_size];
+        let mut points = Vec::with_capacity((count * windows as u64) as usize);
+        for _ in 0..count * windows as u64 {
+            reader
+                .read_exact(&mut bytes)
+                .with_context(|| format!("reading {}", path))?;
+            points.push(P::decode(Encoding::Uncompressed, &bytes)?.to_affine());
+        }
+        Ok(Self {
+            window,
+            windows: windows as usize,
+            points,
+        })
+    }
+
+    /// Number of bases.
+    pub fn len(&self) -> usize {
+        self.points.len() / self.windows
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.points.is_empty()
+    }
+
+    /// Returns the sum of `scalars[i]·base_i`, as `Point::multi_exp` would.
+    pub fn multi_exp(&self, scalars: &[Scalar]) -> Result<P> {
+        if scalars.len() > self.len() {
+            return Err(anyhow!(
+                "{} scalars for a table of {} bases",
+                scalars.len(),
+                self.len()
+            ));
+        }
+        let mut buckets = vec![P::identity(); (1 << self.window) - 1];
+        for (scalar, entries) in scalars.iter().zip(self.points.chunks(self.windows)) {
+            let repr = scalar.to_repr();
+            let bytes = repr.as_ref();
+            for (j, entry) in entries.iter().enumerate() {
+                let digit = digit(bytes, j as u32 * self.window, self.window);
+                if digit != 0 {
+                    buckets[digit - 1] += entry;
+                }
+            }
+        }
+        // sum(k·bucket_k) as the sum of the running sums from the top bucket down.
+        let mut running = P::identity();
+        let mut sum = P::identity();
+        for bucket in buckets.iter().rev() {
+            running += bucket;
+            sum += running;
+        }
+        Ok(sum)
+    }
+}
+
+/// Returns the `width` bits of the little-endian `bytes` starting at bit `start`.
+fn digit(bytes: &[u8], start: u32, width: u32) -> usize {
+    let mut digit = 0;
+    for bit in (start..start + width).rev() {
+        let byte = bytes.get((bit / 8) as usize).copied().unwrap_or(0);
+        digit = digit << 1 | ((byte >> (bit % 8)) & 1) as usize;
+    }
+    digit
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::curve::G1;
+    use ff::Field;
+
+    #[test]
+    fn tables_give_the_naive_msm() {
+        let dir = tempfile::tempdir().unwrap();
+        let tau = Scalar::from(7u64);
+        let layout = Layout::dense(dir.path(), 10, 4);
+        layout.write_set::<G1>(&tau);
+        let bases = layout.compute::<G1>(&tau, 0, 9).unwrap();
+        // Large scalars, so that every window is used, and a zero one.
+        let mut scalars: Vec<Scalar> = (0..9u64)
+            .map(|i| -Scalar::from(i * i + 3) * Scalar::from(1u64 << i))
+            .collect();
+        scalars[4] = Scalar::ZERO;
+        let naive = bases
+            .iter()
+            .zip(&scalars)
+            .map(|(base, scalar)| *base * scalar)
+            .sum::<G1>();
+        for window in [1, 5, 8, MAX_WINDOW] {
+            let path = dir.path().join(format!("table_{}.bin", window));
+            let path = path.to_str().unwrap();
+            write::<G1>(&layout, 9, window, path).unwrap();
+            let table = Table::<G1>::load(path).unwrap();
+            assert_eq!(table.len(), 9);
+            assert_eq!(table.multi_exp(&scalars).unwrap(), naive);
+            assert_eq!(
+                table.multi_exp(&scalars[..4]).unwrap(),
+                G1::multi_exp(&bases[..4], &scalars[..4])
+            );
+            assert!(table.multi_exp(&[Scalar::ONE; 10]).is_err());
+        }
+        assert!(write::<G1>(&layout, 11, 8, dir.path().join("t").to_str().unwrap()).is_err());
+    }
+}
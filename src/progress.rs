@@ -0,0 +1,125 @@
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::Duration;
+
+/// Progress counters of a single curve, shared between its compute thread, its writer thread, and
+/// the reporter.
+#[derive(Debug, Default)]
+pub struct CurveProgress {
+    pub total: AtomicUsize,
+    pub computed: AtomicUsize,
+    pub written: AtomicUsize,
+    pub queued_chunks: AtomicUsize,
+}
+
+/// Point-in-time copy of a `CurveProgress`.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct Snapshot {
+    pub total: usize,
+    pub computed: usize,
+    pub written: usize,
+    pub queued_chunks: usize,
+}
+
+impl CurveProgress {
+    pub fn snapshot(&self) -> Snapshot {
+        Snapshot {
+            total: self.total.load(Ordering::Acquire),
+            computed: self.computed.load(Ordering::Acquire),
+            written: self.written.load(Ordering::Acquire),
+            queued_chunks: self.queued_chunks.load(Ordering::Acquire),
+        }
+    }
+}
+
+/// Estimates the time left to process `remaining` items at the average rate at which `done` items
+/// were processed in `elapsed`. Returns `None` when no rate is known yet.
+pub fn eta(remaining: usize, done: usize, elapsed: Duration) -> Option<Duration> {
+    if remaining == 0 {
+        return Some(Duration::ZERO);
+    }
+    if done == 0 || elapsed.is_zero() {
+        return None;
+    }
+    Some(elapsed.mul_f64(remaining as f64 / done as f64))
+}
+
+/// Separate estimates of when computation ends and when all the resulting chunks are on disk.
+///
+/// Writes can't finish before the computation does, so the write ETA is the larger of the compute
+/// ETA and the time needed to write all outstanding points (queued or not yet computed) at the
+/// measured write rate. A write ETA noticeably larger than the compute ETA means the run is
+/// I/O-bound.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct Etas {
+    pub compute: Option<Duration>,
+    pub write: Option<Duration>,
+}
+
+impl Etas {
+    pub fn new(snapshot: &Snapshot, elapsed: Duration) -> Self {
+        let compute = eta(
+            snapshot.total - snapshot.computed,
+            snapshot.computed,
+            elapsed,
+        );
+        let write = eta(snapshot.total - snapshot.written, snapshot.written, elapsed);
+        Self {
+            compute,
+            write: compute
+                .zip(write)
+                .map(|(compute, write)| compute.max(write)),
+        }
+    }
+
+    /// Combines the ETAs of curves generated concurrently: the slowest one wins.
+    pub fn max(self, other: Self) -> Self {
+        let max = |a: Option<Duration>, b: Option<Duration>| a.zip(b).map(|(a, b)| a.max(b));
+        Self {
+            compute: max(self.compute, other.compute),
+            write: max(self.write, other.write),
+        }
+    }
+}
+
+pub fn format_eta(eta: Option<Duration>) -> String {
+    match eta {
+        Some(eta) => format!("{}s", eta.as_secs()),
+        None => "unknown".to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn snapshot(total: usize, computed: usize, written: usize) -> Snapshot {
+        Snapshot {
+            total,
+            computed,
+            written,
+            queued_chunks: 0,
+        }
+    }
+
+    #[test]
+    fn write_eta_follows_the_slower_write_rate() {
+        let etas = Etas::new(&snapshot(1000, 500, 100), Duration::from_secs(10));
+        assert_eq!(etas.compute, Some(Duration::from_secs(10)));
+        assert_eq!(etas.write, Some(Duration::from_secs(90)));
+    }
+
+    #[test]
+    fn write_eta_is_never_before_the_compute_eta() {
+        let etas = Etas::new(&snapshot(1000, 100, 100), Duration::from_secs(10));
+        assert_eq!(etas.compute, Some(Duration::from_secs(90)));
+        assert_eq!(etas.write, Some(Duration::from_secs(90)));
+    }
+
+    #[test]
+    fn etas_are_unknown_before_any_progress() {
+        let etas = Etas::new(&snapshot(1000, 0, 0), Duration::from_secs(10));
+        assert_eq!(etas, Etas::default());
+        let done = Etas::new(&snapshot(0, 0, 0), Duration::ZERO);
+        assert_eq!(done.write, Some(Duration::ZERO));
+    }
+}
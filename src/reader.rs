@@ -0,0 +1,28 @@
+use crate::chunk;
+use crate::curve::Point;
+use crate::layout::Layout;
+use anyhow::{Result, anyhow};
+
+/// Reads the point at global `index` (i.e. tau^index times the generator, or tau^power(index) in
+/// strided sets) from a set with the given layout.
+pub fn read_point<P: Point>(layout: &Layout, index: u64) -> Result<P> {
+    if index >= layout.count {
+        return Err(anyhow!(
+            "index {} out of range, the {} set has {} points",
+            index,
+            P::NAME,
+            layout.count
+        ));
+    }
+    let path = layout.chunk_path::<P>(index / layout.chunk_length)?;
+    let points = chunk::read::<P>(path.as_str())?;
+    let offset = index % layout.chunk_length;
+    points.get(offset as usize).copied().ok_or_else(|| {
+        anyhow!(
+            "{} has no element at offset {} (index {} out of range)",
+            path,
+            offset,
+            index
+        )
+    })
+}
@@ -0,0 +1,221 @@
+//! Moving a set to a new file pattern or chunk length without regenerating it.
+//!
+//! With an unchanged chunk length the chunk files are simply renamed (or hard-linked when the old
+//! files are kept), as their content doesn't depend on their path; this is fast and doesn't touch
+//! the point data. A new chunk length requires re-chunking: reading every point and writing new
+//! chunk files, which is as slow as reading and writing the whole set. Views of the set still refer
+//! to the old layout and must be written again.
+
+use crate::chunk;
+use crate::curve::Point;
+use crate::layout::Layout;
+use crate::manifest::{ChunkEntry, CurveManifest};
+use anyhow::{Context, Result, anyhow};
+use std::path::Path;
+
+/// How `relayout` moved a set.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Relayout {
+    Unchanged,
+    Renamed,
+    Rechunked,
+}
+
+fn create_parent(path: &str) -> Result<()> {
+    if let Some(parent) = Path::new(path).parent() {
+        std::fs::create_dir_all(parent)
+            .with_context(|| format!("creating {}", parent.display()))?;
+    }
+    Ok(())
+}
+
+/// Moves the file at `from` to `to`, or hard-links it if `keep` is set, falling back to copying
+/// e.g. across file systems.
+fn move_file(from: &str, to: &str, keep: bool) -> Result<()> {
+    create_parent(to)?;
+    let moved = if keep {
+        std::fs::hard_link(from, to)
+    } else {
+        std::fs::rename(from, to)
+    };
+    if moved.is_err() {
+        std::fs::copy(from, to).with_context(|| format!("copying {} to {}", from, to))?;
+        if !keep {
+            std::fs::remove_file(from).with_context(|| format!("removing {}", from))?;
+        }
+    }
+    Ok(())
+}
+
+/// Moves the `P` set described by `curve` to file pattern `pattern` and chunks of `chunk_length`
+/// points, updating `curve` accordingly. The old files are removed unless `keep` is set.
+pub fn relayout<P: Point>(
+    curve: &mut CurveManifest,
+    pattern: &str,
+    chunk_length: u64,
+    keep: bool,
+) -> Result<Relayout> {
+    if chunk_length < 2 {
+        return Err(anyhow!("each chunk must have at least 2 elements"));
+    }
+    let old = curve.layout.clone();
+    let new = Layout {
+        pattern: pattern.to_string(),
+        chunk_length,
+        ..old.clone()
+    };
+    if new == old {
+        return Ok(Relayout::Unchanged);
+    }
+    let old_paths = (0..old.chunk_count())
+        .map(|index| old.chunk_path::<P>(index))
+        .collect::<Result<Vec<_>>>()?;
+    let new_paths = (0..new.chunk_count())
+        .map(|index| new.chunk_path::<P>(index))
+        .collect::<Result<Vec<_>>>()?;
+    for (index, path) in new_paths.iter().enumerate() {
+        if let Some(other) = new_paths[..index].iter().position(|other| other == path) {
+            return Err(anyhow!(
+                "{} maps {} chunks {} and {} to the same path",
+                pattern,
+                P::NAME,
+                other,
+                index
+            ));
+        }
+    }
+
+    if chunk_length == old.chunk_length {
+        // Chunk K may keep its path, but mustn't take the path of another chunk.
+        for (index, path) in new_paths.iter().enumerate() {
+            if let Some(other) = old_paths.iter().position(|old_path| old_path == path)
+                && other != index
+            {
+                return Err(anyhow!(
+                    "{} chunk {} would overwrite chunk {} at {}",
+                    P::NAME,
+                    index,
+                    other,
+                    path
+                ));
+            }
+        }
+        for entry in &mut curve.chunks {
+            let index = entry.index as usize;
+            if old_paths[index] != new_paths[index] {
+                move_file(old_paths[index].as_str(), new_paths[index].as_str(), keep)?;
+            }
+            entry.path = new_paths[index].clone();
+        }
+        curve.layout = new;
+        return Ok(Relayout::Renamed);
+    }
+
+    if curve.hiding.is_some() {
+        return Err(anyhow!(
+            "the {} set has a hiding key, which can't be re-chunked",
+            P::NAME
+        ));
+    }
+    if let Some(path) = new_paths.iter().find(|path| old_paths.contains(path)) {
+        return Err(anyhow!(
+            "re-chunking would overwrite {} before it's read, pick another pattern",
+            path
+        ));
+    }
+    let mut chunks = vec![];
+    let mut points = Vec::with_capacity(chunk_length as usize);
+    for (old_index, old_path) in old_paths.iter().enumerate() {
+        let old_points = chunk::read::<P>(old_path.as_str())?;
+        if old_points.len() as u64 != old.chunk_size(old_index as u64) {
+            return Err(anyhow!(
+                "{} has {} points, expected {}",
+                old_path,
+                old_points.len(),
+                old.chunk_size(old_index as u64)
+            ));
+        }
+        for point in old_points {
+            points.push(point);
+            let index = chunks.len() as u64;
+            if points.len() as u64 == new.chunk_size(index) {
+                let path = new_paths[index as usize].as_str();
+                create_parent(path)?;
+                let blake2b = chunk::write(
+                    path,
+                    &points,
+                    new.format,
+                    new.format_version,
+                    new.block_size,
+                    new.encoding,
+                    new.chunk_start(index),
+                )?;
+                chunks.push(ChunkEntry {
+                    index,
+                    path: path.to_string(),
+                    count: points.len() as u64,
+                    blake2b,
+                    provenance: None,
+                });
+                points.clear();
+            }
+        }
+    }
+    if !keep {
+        for path in &old_paths {
+            std::fs::remove_file(path).with_context(|| format!("removing {}", path))?;
+        }
+    }
+    curve.layout = new;
+    curve.chunks = chunks;
+    Ok(Relayout::Rechunked)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::curve::G1;
+    use blstrs::Scalar;
+
+    fn points(curve: &CurveManifest) -> Vec<G1> {
+        curve
+            .chunks
+            .iter()
+            .flat_map(|entry| chunk::read::<G1>(entry.path.as_str()).unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn relayout_preserves_the_points_and_updates_the_paths() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut curve = Layout::dense(dir.path(), 10, 4).write_set::<G1>(&Scalar::from(7u64));
+        let expected = points(&curve);
+        let old = curve.clone();
+
+        let pattern = format!("{}/params/g1_{{index:08}}.bin", dir.path().display());
+        let moved = relayout::<G1>(&mut curve, pattern.as_str(), 4, false).unwrap();
+        assert_eq!(moved, Relayout::Renamed);
+        assert_eq!(curve.layout.pattern, pattern);
+        for (entry, old) in curve.chunks.iter().zip(&old.chunks) {
+            assert_eq!(
+                entry.path,
+                curve.layout.chunk_path::<G1>(entry.index).unwrap()
+            );
+            assert!(
+                entry
+                    .path
+                    .ends_with(&format!("params/g1_{:08}.bin", entry.index))
+            );
+            assert_eq!(entry.blake2b, old.blake2b);
+            assert!(!Path::new(old.path.as_str()).exists());
+        }
+        assert_eq!(points(&curve), expected);
+
+        let pattern = format!("{}/g1_by_3_{{}}.bin", dir.path().display());
+        let moved = relayout::<G1>(&mut curve, pattern.as_str(), 3, false).unwrap();
+        assert_eq!(moved, Relayout::Rechunked);
+        assert_eq!(curve.chunks.len(), 4);
+        assert_eq!(curve.layout.chunk_length, 3);
+        assert_eq!(points(&curve), expected);
+    }
+}
@@ -0,0 +1,140 @@
+use crate::chunk;
+use crate::curve::Point;
+use crate::manifest::{ChunkEntry, CurveManifest, Manifest};
+use crate::powers;
+use crate::validate::Problem;
+use anyhow::{Result, anyhow};
+use blstrs::Scalar;
+use std::collections::{BTreeMap, BTreeSet};
+
+/// Checks `tau` against a chunk other than `indices`, so that a wrong tau is detected before it
+/// overwrites anything. Returns false if the set has no other chunk to check against.
+fn check_tau<P: Point>(tau: &Scalar, curve: &CurveManifest, indices: &[usize]) -> Result<bool> {
+    let layout = &curve.layout;
+    let Some(reference) =
+        (0..layout.chunk_count()).find(|&reference| !indices.contains(&(reference as usize)))
+    else {
+        return Ok(false);
+    };
+    let path = layout.chunk_path::<P>(reference)?;
+    let points = chunk::read::<P>(path.as_str())?;
+    let start = layout.chunk_start(reference);
+    let Some(index) = layout.checkable_index(start, points.len() as u64) else {
+        return Ok(false);
+    };
+    if points.get((index - start) as usize)
+        != powers::compute_from(layout.base::<P>()?, tau, layout.power(index), 1).first()
+    {
+        return Err(anyhow!(
+            "the provided tau doesn't match {} (element {}), refusing to repair",
+            path,
+            index
+        ));
+    }
+    Ok(true)
+}
+
+/// Chunks rewritten by `repair_chunks`.
+#[derive(Debug)]
+pub struct Repaired {
+    pub paths: Vec<String>,
+    /// Whether the provided tau was checked against a chunk left alone before rewriting any, which
+    /// is impossible if every chunk was repaired.
+    pub tau_checked: bool,
+}
+
+/// Recomputes chunk `index` of the `P` set described by `manifest`, rewrites it, and updates its
+/// manifest entry.
+pub fn repair_chunk<P: Point>(
+    tau: &Scalar,
+    manifest: &mut Manifest,
+    index: usize,
+) -> Result<Repaired> {
+    repair_chunks::<P>(tau, manifest, &[index])
+}
+
+/// Like `repair_chunk`, for several chunks.
+pub fn repair_chunks<P: Point>(
+    tau: &Scalar,
+    manifest: &mut Manifest,
+    indices: &[usize],
+) -> Result<Repaired> {
+    let curve = manifest.curve_mut::<P>()?;
+    let layout = curve.layout.clone();
+    if let Some(&index) = indices
+        .iter()
+        .find(|&&index| index as u64 >= layout.chunk_count())
+    {
+        return Err(anyhow!(
+            "chunk {} is out of range, the {} set has {} points in chunks of {}",
+            index,
+            P::NAME,
+            layout.count,
+            layout.chunk_length
+        ));
+    }
+    let tau_checked = check_tau::<P>(tau, curve, indices)?;
+    let mut paths = vec![];
+    for &index in indices {
+        let first = layout.chunk_start(index as u64);
+        let count = layout.chunk_size(index as u64);
+        let points = layout.compute::<P>(tau, first, count)?;
+        let path = layout.chunk_path::<P>(index as u64)?;
+        let blake2b = chunk::write(
+            path.as_str(),
+            &points,
+            layout.format,
+            layout.format_version,
+            layout.block_size,
+            layout.encoding,
+            first,
+        )?;
+        curve.set_chunk(ChunkEntry {
+            index: index as u64,
+            path: path.clone(),
+            count,
+            blake2b,
+            provenance: None,
+        });
+        paths.push(path);
+    }
+    Ok(Repaired { paths, tau_checked })
+}
+
+/// Renders the chunks `problems` are attributed to as a failure list, as written by `validate
+/// --failures` and read by `repair`: one line per chunk, with the curve name and the chunk index
+/// separated by a space, e.g. `G1 3`.
+pub fn format_failures<'a>(problems: impl IntoIterator<Item = (&'a str, &'a Problem)>) -> String {
+    let failures: BTreeSet<(&str, u64)> = problems
+        .into_iter()
+        .filter_map(|(curve, problem)| Some((curve, problem.chunk?)))
+        .collect();
+    failures
+        .into_iter()
+        .map(|(curve, index)| format!("{} {}\n", curve, index))
+        .collect()
+}
+
+/// Parses a failure list written by `format_failures`, returning the chunk indices to repair keyed
+/// by curve name. Blank lines and lines starting with `#` are ignored.
+pub fn parse_failures(list: &str) -> Result<BTreeMap<String, Vec<usize>>> {
+    let mut failures: BTreeMap<String, Vec<usize>> = BTreeMap::new();
+    for (number, line) in list.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let (curve, index) = line
+            .split_once(' ')
+            .ok_or_else(|| anyhow!("line {}: expected a curve and a chunk index", number + 1))?;
+        let index = index
+            .trim()
+            .parse()
+            .map_err(|error| anyhow!("line {}: invalid chunk index: {}", number + 1, error))?;
+        let indices = failures.entry(curve.to_string()).or_default();
+        if !indices.contains(&index) {
+            indices.push(index);
+        }
+    }
+    Ok(failures)
+}
@@ -0,0 +1,140 @@
+//! Resumption of interrupted generation runs.
+//!
+//! Chunk files are written atomically (see `chunk::write`), so any chunk file found on disk is
+//! complete. An interrupted run can therefore restart from the first chunk that's missing or that
+//! doesn't match the layout, reusing all the chunks before it.
+//!
+//! A run interrupted by a signal in spot mode also checkpoints the points of the chunk it was
+//! computing to a partial file in the scratch format (see `partial_path`), from which the next run
+//! picks up.
+
+use crate::chunk::{self, Format};
+use crate::curve::Point;
+use crate::layout::Layout;
+use crate::manifest::{self, ChunkEntry};
+use crate::powers;
+use crate::scratch;
+use anyhow::{Context, Result, anyhow};
+use blstrs::Scalar;
+
+/// Returns the path of the partial checkpoint of chunk `index`.
+pub fn partial_path<P: Point>(layout: &Layout, index: u64) -> Result<String> {
+    Ok(format!("{}.partial", layout.chunk_path::<P>(index)?))
+}
+
+/// Returns the points checkpointed in the partial file of chunk `index` of `layout`, if any. One
+/// of them is checked against `tau` like in `complete_chunks`.
+pub fn partial_chunk<P: Point>(tau: &Scalar, layout: &Layout, index: u64) -> Result<Vec<P>> {
+    let path = partial_path::<P>(layout, index)?;
+    if index >= layout.chunk_count() || !std::path::Path::new(path.as_str()).exists() {
+        return Ok(vec![]);
+    }
+    let (first_index, points) = scratch::read::<P>(path.as_str())?;
+    if first_index != layout.chunk_start(index) || points.len() as u64 >= layout.chunk_size(index) {
+        return Err(anyhow!("{} doesn't match the layout of the set", path));
+    }
+    if let Some(checked) = layout.checkable_index(first_index, points.len() as u64) {
+        let point = &points[(checked - first_index) as usize];
+        let exponent = layout.power(checked);
+        if powers::compute_from(layout.base::<P>()?, tau, exponent, 1).first() != Some(point) {
+            return Err(anyhow!(
+                "{} wasn't generated with the provided tau, refusing to resume",
+                path
+            ));
+        }
+    }
+    Ok(points)
+}
+
+/// Returns the format version of the first chunk of `layout` if it's on disk, so that a resumed run
+/// writes the rest of the set in the same version.
+pub fn format_version<P: Point>(layout: &Layout) -> Result<Option<u8>> {
+    let path = layout.chunk_path::<P>(0)?;
+    if !std::path::Path::new(path.as_str()).exists() {
+        return Ok(None);
+    }
+    // A chunk that can't be read is regenerated, see `read_complete`.
+    Ok(chunk::read_file_header(path.as_str())
+        .ok()
+        .map(|header| header.format_version()))
+}
+
+/// Reads chunk `index` of `layout` if it's on disk with the expected header and size, returning
+/// its manifest entry along with the point at its `Layout::checkable_index`, if any.
+fn read_complete<P: Point>(layout: &Layout, index: u64) -> Result<Option<(ChunkEntry, Option<P>)>> {
+    let path = layout.chunk_path::<P>(index)?;
+    let data = match std::fs::read(path.as_str()) {
+        Ok(data) => data,
+        Err(error) if error.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+        Err(error) => return Err(error).with_context(|| format!("reading {}", path)),
+    };
+    if Format::detect(&data).ok() != Some(layout.format) {
+        return Ok(None);
+    }
+    let Ok((header, points)) = chunk::parse(&data) else {
+        return Ok(None);
+    };
+    let count = layout.chunk_size(index);
+    let size = P::encoded_size(layout.encoding);
+    let length = (count * size as u64) as usize;
+    if header.curve != P::NAME
+        || header.format_version() != layout.format_version
+        || header.encoding != layout.encoding
+        || header.first_index != layout.chunk_start(index)
+        || header.count != count
+        || data.len() as u64 != layout.plan_chunk::<P>(index)?.byte_size
+        || points.len() < length
+    {
+        return Ok(None);
+    }
+    let start = layout.chunk_start(index);
+    let checked = match layout.checkable_index(start, count) {
+        Some(checked) => {
+            let offset = (checked - start) as usize * size;
+            let point = P::decode(layout.encoding, &points[offset..offset + size])
+                .with_context(|| format!("decoding {}", path))?;
+            Some(point)
+        }
+        None => None,
+    };
+    let entry = ChunkEntry {
+        index,
+        blake2b: manifest::hash(&data),
+        path,
+        count,
+        provenance: None,
+    };
+    Ok(Some((entry, checked)))
+}
+
+/// Returns the manifest entries of the leading chunks of `layout` that are already complete on
+/// disk, so that generation can resume right after them.
+///
+/// A point of the last reused chunk that depends on tau is checked against `tau`, so that a run is
+/// never resumed with a different tau than the one it was started with.
+pub fn complete_chunks<P: Point>(tau: &Scalar, layout: &Layout) -> Result<Vec<ChunkEntry>> {
+    let mut entries = vec![];
+    let mut checked = None;
+    for index in 0..layout.chunk_count() {
+        let Some((entry, point)) = read_complete::<P>(layout, index)? else {
+            break;
+        };
+        if let Some(point) = point {
+            let start = layout.chunk_start(index);
+            checked = layout
+                .checkable_index(start, entry.count)
+                .map(|index| (entry.path.clone(), index, point));
+        }
+        entries.push(entry);
+    }
+    if let Some((path, index, point)) = checked {
+        let exponent = layout.power(index);
+        if powers::compute_from(layout.base::<P>()?, tau, exponent, 1).first() != Some(&point) {
+            return Err(anyhow!(
+                "{} wasn't generated with the provided tau, refusing to resume",
+                path
+            ));
+        }
+    }
+    Ok(entries)
+}
@@ -0,0 +1,280 @@
+//! Scratch format for two-phase generation.
+//!
+//! The first phase dumps raw projective coordinates with no normalization or compression, so the
+//! generation loop never pays for a field inversion. The `finalize` phase later converts each
+//! scratch file into a regular chunk file. Scratch files are independent of each other and can be
+//! finalized in any order, by any number of concurrent processes.
+//!
+//! A scratch file is a bincode-encoded `Header` followed by `count` points of
+//! `Point::SCRATCH_SIZE` bytes each.
+//!
+//! A scratch run also writes a scratch manifest, e.g. `params.scratch.json`, recording the layout
+//! of each curve and its scratch files, from which `finalize` writes the chunk files and the set
+//! manifest the run would have written without `--scratch`.
+
+use crate::chunk::{self, Format};
+use crate::curve::{Encoding, G1, G2, Point};
+use crate::layout::Layout;
+use crate::manifest::{ChunkEntry, CurveManifest, Manifest};
+use crate::pattern::{self, Fields};
+use anyhow::{Context, Result, anyhow};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs::File;
+use std::io::{BufReader, Read};
+
+const MAGIC: [u8; 8] = *b"GPSCRTCH";
+
+pub const VERSION: u32 = 1;
+
+#[derive(Debug, Serialize, Deserialize)]
+struct Header {
+    magic: [u8; 8],
+    curve: String,
+    chunk_index: u64,
+    first_index: u64,
+    count: u64,
+}
+
+/// Writes chunk `chunk_index`, starting at global index `first_index`, in the scratch format. The
+/// file is written atomically, so that a run killed while checkpointing leaves either the previous
+/// file or the complete new one.
+pub fn write<P: Point>(
+    path: &str,
+    chunk_index: usize,
+    first_index: u64,
+    points: &[P],
+) -> Result<()> {
+    let header = Header {
+        magic: MAGIC,
+        curve: P::NAME.to_string(),
+        chunk_index: chunk_index as u64,
+        first_index,
+        count: points.len() as u64,
+    };
+    let mut data = bincode::serde::encode_to_vec(&header, bincode::config::standard())?;
+    data.reserve(points.len() * P::SCRATCH_SIZE);
+    for point in points {
+        point.write_scratch(&mut data);
+    }
+    chunk::write_atomically(path, &data)
+}
+
+fn read_points<P: Point>(reader: &mut impl Read, count: usize) -> Result<Vec<P>> {
+    let mut data = vec![0u8; count * P::SCRATCH_SIZE];
+    reader.read_exact(&mut data)?;
+    data.chunks_exact(P::SCRATCH_SIZE)
+        .map(P::read_scratch)
+        .collect()
+}
+
+/// Reads the scratch file at `path`, returning the global index of its first point and its points.
+pub fn read<P: Point>(path: &str) -> Result<(u64, Vec<P>)> {
+    let mut reader = BufReader::new(File::open(path)?);
+    let header: Header =
+        bincode::serde::decode_from_std_read(&mut reader, bincode::config::standard())?;
+    if header.magic != MAGIC {
+        return Err(anyhow!("{} is not a scratch file", path));
+    }
+    if header.curve != P::NAME {
+        return Err(anyhow!(
+            "{} contains {} points, expected {}",
+            path,
+            header.curve,
+            P::NAME
+        ));
+    }
+    let points = read_points::<P>(&mut reader, header.count as usize)?;
+    Ok((header.first_index, points))
+}
+
+/// Converts the scratch file at `path` into a regular chunk file, picking the output pattern and
+/// encoding according to the curve recorded in the scratch header. Returns the path of the written
+/// chunk.
+pub fn finalize(
+    path: &str,
+    (format, version): (Format, u8),
+    (g1_pattern, g1_encoding): (&str, Encoding),
+    (g2_pattern, g2_encoding): (&str, Encoding),
+) -> Result<String> {
+    let mut reader = BufReader::new(File::open(path)?);
+    let header: Header =
+        bincode::serde::decode_from_std_read(&mut reader, bincode::config::standard())?;
+    if header.magic != MAGIC {
+        return Err(anyhow!("{} is not a scratch file", path));
+    }
+    match header.curve.as_str() {
+        G1::NAME => finalize_points::<G1>(
+            &mut reader,
+            &header,
+            g1_pattern,
+            (format, version),
+            g1_encoding,
+        ),
+        G2::NAME => finalize_points::<G2>(
+            &mut reader,
+            &header,
+            g2_pattern,
+            (format, version),
+            g2_encoding,
+        ),
+        curve => Err(anyhow!("{}: unknown curve {}", path, curve)),
+    }
+}
+
+fn finalize_points<P: Point>(
+    reader: &mut impl Read,
+    header: &Header,
+    pattern: &str,
+    (format, version): (Format, u8),
+    encoding: Encoding,
+) -> Result<String> {
+    let output = pattern::render(
+        pattern,
+        &Fields {
+            curve: P::NAME,
+            index: header.chunk_index,
+            start: header.first_index,
+            count: header.count,
+            encoding: encoding.name(),
+        },
+    )?;
+    let points = read_points::<P>(reader, header.count as usize)?;
+    chunk::write(
+        &output,
+        &points,
+        format,
+        version,
+        None,
+        encoding,
+        header.first_index,
+    )?;
+    Ok(output)
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ScratchCurve {
+    /// Layout of the set, whose chunk paths are those of the scratch files.
+    pub layout: Layout,
+    /// Paths of the scratch files, sorted by chunk index.
+    pub files: Vec<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ScratchManifest {
+    pub version: u32,
+    /// Curves keyed by curve name ("G1", "G2").
+    pub curves: BTreeMap<String, ScratchCurve>,
+}
+
+/// Returns the path of the scratch manifest of a run whose manifest path is `manifest_path`, e.g.
+/// `params.scratch.json` for `params.json`.
+pub fn manifest_path(manifest_path: &str) -> String {
+    let stem = manifest_path.strip_suffix(".json").unwrap_or(manifest_path);
+    format!("{}.scratch.json", stem)
+}
+
+/// Finalizes the scratch files of `curve` in place, with the encoding and format of its layout.
+fn finalize_curve<P: Point>(curve: &ScratchCurve) -> Result<CurveManifest> {
+    let layout = &curve.layout;
+    if curve.files.len() as u64 != layout.chunk_count() {
+        return Err(anyhow!(
+            "the {} set has {} chunks, the scratch manifest lists {} files",
+            P::NAME,
+            layout.chunk_count(),
+            curve.files.len()
+        ));
+    }
+    let mut chunks = vec![];
+    for (index, path) in (0..).zip(&curve.files) {
+        let (first_index, points) = read::<P>(path).with_context(|| format!("reading {}", path))?;
+        if first_index != layout.chunk_start(index)
+            || points.len() as u64 != layout.chunk_size(index)
+        {
+            return Err(anyhow!("{} doesn't match the layout of the set", path));
+        }
+        let output = layout.chunk_path::<P>(index)?;
+        let blake2b = chunk::write(
+            output.as_str(),
+            &points,
+            layout.format,
+            layout.format_version,
+            layout.block_size,
+            layout.encoding,
+            first_index,
+        )?;
+        chunks.push(ChunkEntry {
+            index,
+            path: output,
+            count: points.len() as u64,
+            blake2b,
+            provenance: None,
+        });
+    }
+    Ok(CurveManifest {
+        layout: layout.clone(),
+        chunks,
+        index_file: None,
+        hiding: None,
+    })
+}
+
+impl ScratchManifest {
+    /// Records the layouts of `manifest`, the set manifest of a scratch run.
+    pub fn new(manifest: &Manifest) -> Result<Self> {
+        let mut curves = BTreeMap::new();
+        for (name, curve) in &manifest.curves {
+            let layout = &curve.layout;
+            let files = (0..layout.chunk_count())
+                .map(|index| match name.as_str() {
+                    G1::NAME => layout.chunk_path::<G1>(index),
+                    G2::NAME => layout.chunk_path::<G2>(index),
+                    _ => Err(anyhow!("unknown curve {}", name)),
+                })
+                .collect::<Result<_>>()?;
+            let curve = ScratchCurve {
+                layout: layout.clone(),
+                files,
+            };
+            curves.insert(name.clone(), curve);
+        }
+        Ok(Self {
+            version: VERSION,
+            curves,
+        })
+    }
+
+    /// Finalizes all the scratch files, returning the set manifest.
+    pub fn finalize(&self) -> Result<Manifest> {
+        let mut manifest = Manifest::default();
+        for (name, curve) in &self.curves {
+            let curve = match name.as_str() {
+                G1::NAME => finalize_curve::<G1>(curve)?,
+                G2::NAME => finalize_curve::<G2>(curve)?,
+                _ => return Err(anyhow!("unknown curve {}", name)),
+            };
+            manifest.curves.insert(name.clone(), curve);
+        }
+        Ok(manifest)
+    }
+
+    pub fn load(path: &str) -> Result<Self> {
+        let json = std::fs::read_to_string(path).with_context(|| format!("reading {}", path))?;
+        let manifest: Self =
+            serde_json::from_str(json.as_str()).with_context(|| format!("parsing {}", path))?;
+        if manifest.version != VERSION {
+            return Err(anyhow!(
+                "{}: unsupported scratch manifest version {}",
+                path,
+                manifest.version
+            ));
+        }
+        Ok(manifest)
+    }
+
+    pub fn save(&self, path: &str) -> Result<()> {
+        let mut json = serde_json::to_string_pretty(self)?;
+        json.push('\n');
+        std::fs::write(path, json).with_context(|| format!("writing {}", path))
+    }
+}
@@ -0,0 +1,149 @@
+//! Passphrase-encrypted tau files.
+//!
+//! G2 points are several times slower to compute than G1 points, so a set may be generated in two
+//! runs, e.g. G1 now and G2 later on a bigger machine. Both runs need the same tau, which the first
+//! run writes sealed with a passphrase: the key is derived from the passphrase with Argon2id and a
+//! random salt, and tau is encrypted with ChaCha20-Poly1305 under a random nonce. The Argon2
+//! parameters are stored with the salt, so that files stay readable if the defaults change.
+//!
+//! A sealed tau is only as strong as its passphrase. It must be deleted, like any other copy of
+//! tau, once the set is complete.
+
+use crate::tau::SecretScalar;
+use anyhow::{Context, Result, anyhow};
+use argon2::{Algorithm, Argon2, Params, Version};
+use blstrs::Scalar;
+use chacha20poly1305::aead::{Aead, KeyInit, Payload};
+use chacha20poly1305::{ChaCha20Poly1305, Key, Nonce};
+use serde::{Deserialize, Serialize};
+use zeroize::Zeroizing;
+
+pub const VERSION: u32 = 1;
+
+/// Associated data of the ciphertext, for domain separation.
+const CONTEXT: &[u8] = b"GENERATE_PARAMS_SEALED_TAU";
+
+/// Argon2id memory cost in KiB, time cost, and parallelism of new files.
+const MEMORY_COST: u32 = 64 * 1024;
+const TIME_COST: u32 = 3;
+const PARALLELISM: u32 = 1;
+
+const SALT_LENGTH: usize = 16;
+const NONCE_LENGTH: usize = 12;
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SealedTau {
+    pub version: u32,
+    pub memory_cost: u32,
+    pub time_cost: u32,
+    pub parallelism: u32,
+    /// Hex-encoded Argon2id salt.
+    pub salt: String,
+    /// Hex-encoded ChaCha20-Poly1305 nonce.
+    pub nonce: String,
+    /// Hex-encoded encryption of the big-endian bytes of tau, followed by the tag.
+    pub ciphertext: String,
+}
+
+fn random_bytes<const N: usize>() -> Result<[u8; N]> {
+    let mut bytes = [0u8; N];
+    getrandom::fill(&mut bytes)
+        .map_err(|error| anyhow!("no system randomness available: {}", error))?;
+    Ok(bytes)
+}
+
+fn decode_hex(hex: &str, name: &str) -> Result<Vec<u8>> {
+    hex::decode(hex).map_err(|error| anyhow!("invalid {} hex: {}", name, error))
+}
+
+impl SealedTau {
+    /// Derives the encryption key from `passphrase` with the parameters and salt of `self`.
+    fn key(&self, passphrase: &[u8]) -> Result<Zeroizing<[u8; 32]>> {
+        let params = Params::new(self.memory_cost, self.time_cost, self.parallelism, Some(32))
+            .map_err(|error| anyhow!("invalid Argon2 parameters: {}", error))?;
+        let salt = decode_hex(self.salt.as_str(), "salt")?;
+        let mut key = Zeroizing::new([0u8; 32]);
+        Argon2::new(Algorithm::Argon2id, Version::V0x13, params)
+            .hash_password_into(passphrase, &salt, key.as_mut())
+            .map_err(|error| anyhow!("deriving the key: {}", error))?;
+        Ok(key)
+    }
+
+    /// Encrypts `tau` with `passphrase`.
+    pub fn seal(tau: &Scalar, passphrase: &[u8]) -> Result<Self> {
+        if passphrase.is_empty() {
+            return Err(anyhow!("the passphrase is empty"));
+        }
+        let nonce = random_bytes::<NONCE_LENGTH>()?;
+        let mut sealed = Self {
+            version: VERSION,
+            memory_cost: MEMORY_COST,
+            time_cost: TIME_COST,
+            parallelism: PARALLELISM,
+            salt: hex::encode(random_bytes::<SALT_LENGTH>()?),
+            nonce: hex::encode(nonce),
+            ciphertext: String::new(),
+        };
+        let key = sealed.key(passphrase)?;
+        let plaintext = Zeroizing::new(tau.to_bytes_be());
+        let ciphertext = ChaCha20Poly1305::new(&Key::from(*key))
+            .encrypt(
+                &Nonce::from(nonce),
+                Payload {
+                    msg: plaintext.as_slice(),
+                    aad: CONTEXT,
+                },
+            )
+            .map_err(|error| anyhow!("encrypting tau: {}", error))?;
+        sealed.ciphertext = hex::encode(ciphertext);
+        Ok(sealed)
+    }
+
+    /// Decrypts tau with `passphrase`.
+    pub fn open(&self, passphrase: &[u8]) -> Result<SecretScalar> {
+        let key = self.key(passphrase)?;
+        let nonce: [u8; NONCE_LENGTH] = decode_hex(self.nonce.as_str(), "nonce")?
+            .try_into()
+            .map_err(|_| anyhow!("the nonce must be {} bytes long", NONCE_LENGTH))?;
+        let ciphertext = decode_hex(self.ciphertext.as_str(), "ciphertext")?;
+        let plaintext = Zeroizing::new(
+            ChaCha20Poly1305::new(&Key::from(*key))
+                .decrypt(
+                    &Nonce::from(nonce),
+                    Payload {
+                        msg: ciphertext.as_slice(),
+                        aad: CONTEXT,
+                    },
+                )
+                .map_err(|_| anyhow!("wrong passphrase, or the sealed tau was altered"))?,
+        );
+        let bytes: &[u8; 32] = plaintext
+            .as_slice()
+            .try_into()
+            .map_err(|_| anyhow!("the sealed tau isn't 32 bytes long"))?;
+        Scalar::from_bytes_be(bytes)
+            .into_option()
+            .map(SecretScalar::new)
+            .ok_or_else(|| anyhow!("the sealed tau is not less than the group order"))
+    }
+
+    pub fn load(path: &str) -> Result<Self> {
+        let json = std::fs::read_to_string(path).with_context(|| format!("reading {}", path))?;
+        let sealed: Self =
+            serde_json::from_str(json.as_str()).with_context(|| format!("parsing {}", path))?;
+        if sealed.version != VERSION {
+            return Err(anyhow!(
+                "{}: unsupported sealed tau version {}",
+                path,
+                sealed.version
+            ));
+        }
+        Ok(sealed)
+    }
+
+    pub fn save(&self, path: &str) -> Result<()> {
+        let mut json = serde_json::to_string_pretty(self)?;
+        json.push('\n');
+        std::fs::write(path, json).with_context(|| format!("writing {}", path))
+    }
+}
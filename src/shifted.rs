@@ -0,0 +1,306 @@
+//! Bundles of sets sharing tau, one of them shifted by a known power.
+//!
+//! Some schemes need the powers tau^i·G alongside the shifted powers tau^(k+i)·G, e.g. to enforce
+//! degree bounds, and mixing files from sets with different taus breaks them silently. A bundle
+//! is a directory holding both sets, generated in one go, and a top-level `bundle.json` that ties
+//! them to one tau: it records the tau handle, tau·G1 and tau·G2, which identify tau without
+//! revealing it, the shift k with the verification element tau^k·G2, and the fingerprint of the
+//! manifest of each set.
+//!
+//! `check` verifies a bundle without tau: every chunk of both sets must hold consecutive powers of
+//! the tau of the handle, the base set must start with G and the handle, and the shifted set with
+//! point k of the base set in G1 and the verification element in G2, which pairings against point
+//! k of the base set tie to the same power.
+
+use crate::ceremony;
+use crate::chunk::{self, Format};
+use crate::curve::{Encoding, G1, G2, Point};
+use crate::layout::Layout;
+use crate::manifest::{ChunkEntry, CurveManifest, Manifest};
+use crate::powers;
+use crate::reader;
+use crate::validate::{self, Problem};
+use anyhow::{Context, Result, anyhow};
+use blstrs::Scalar;
+use group::Group;
+use serde::{Deserialize, Serialize};
+
+pub const VERSION: u32 = 1;
+
+/// Labels of the sets of a bundle, which are also the names of their directories.
+pub const BASE: &str = "base";
+pub const SHIFTED: &str = "shifted";
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SubSet {
+    pub label: String,
+    /// Path of the manifest of the set.
+    pub manifest: String,
+    pub fingerprint: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ShiftedBundle {
+    pub version: u32,
+    /// Hex-encoded compressed tau·G1 and tau·G2.
+    pub tau_g1: String,
+    pub tau_g2: String,
+    pub shift: u64,
+    /// Hex-encoded compressed tau^shift·G2.
+    pub shift_g2: String,
+    pub sets: Vec<SubSet>,
+}
+
+/// Returns the path of the top-level manifest of the bundle in `dir`.
+pub fn path(dir: &str) -> String {
+    format!("{}/bundle.json", dir)
+}
+
+fn encode_point<P: Point>(point: &P) -> String {
+    let mut bytes = vec![];
+    point.encode(Encoding::Compressed, &mut bytes);
+    hex::encode(bytes)
+}
+
+fn decode_point<P: Point>(hex: &str, name: &str) -> Result<P> {
+    let bytes = hex::decode(hex).map_err(|error| anyhow!("invalid {} hex: {}", name, error))?;
+    P::decode(Encoding::Compressed, &bytes).with_context(|| format!("parsing {}", name))
+}
+
+/// Computes and writes all the points of the `P` set described by `layout`.
+fn write_set<P: Point>(tau: &Scalar, layout: Layout) -> Result<CurveManifest> {
+    let mut chunks = vec![];
+    for index in 0..layout.chunk_count() {
+        let first = layout.chunk_start(index);
+        let count = layout.chunk_size(index);
+        let points = layout.compute::<P>(tau, first, count)?;
+        let path = layout.chunk_path::<P>(index)?;
+        let blake2b = chunk::write(
+            path.as_str(),
+            &points,
+            layout.format,
+            layout.format_version,
+            layout.block_size,
+            layout.encoding,
+            first,
+        )?;
+        chunks.push(ChunkEntry {
+            index,
+            path,
+            count,
+            blake2b,
+            provenance: None,
+        });
+    }
+    Ok(CurveManifest {
+        layout,
+        chunks,
+        index_file: None,
+        hiding: None,
+    })
+}
+
+impl ShiftedBundle {
+    /// Generates `g1_count` G1 and `g2_count` G2 powers of `tau` from tau^0 and from tau^shift, in
+    /// chunks of `chunk_length` points, as a bundle in `dir`, and writes its top-level manifest.
+    pub fn write(
+        tau: &Scalar,
+        dir: &str,
+        g1_count: u64,
+        g2_count: u64,
+        shift: u64,
+        chunk_length: u64,
+    ) -> Result<Self> {
+        if shift == 0 || shift >= g1_count {
+            return Err(anyhow!(
+                "the shift must be between 1 and the G1 count minus 1, so that the shifted set \
+                 can be checked against the base set"
+            ));
+        }
+        if g2_count == 0 || chunk_length == 0 {
+            return Err(anyhow!(
+                "the G2 count and the chunk length must be positive"
+            ));
+        }
+        let mut sets = vec![];
+        for (label, offset) in [(BASE, 0), (SHIFTED, shift)] {
+            let set_dir = format!("{}/{}", dir, label);
+            std::fs::create_dir_all(set_dir.as_str())
+                .with_context(|| format!("creating {}", set_dir))?;
+            let layout = |name: &str, count: u64| Layout {
+                count,
+                chunk_length,
+                pattern: format!("{}/{}_{{}}.bin", set_dir, name.to_lowercase()),
+                encoding: Encoding::Compressed,
+                format: Format::default(),
+                format_version: chunk::FORMAT_VERSION,
+                offset,
+                stride: 1,
+                block_size: None,
+                generator: None,
+                reverse: false,
+            };
+            let mut manifest = Manifest::default();
+            manifest.curves.insert(
+                G1::NAME.to_string(),
+                write_set::<G1>(tau, layout(G1::NAME, g1_count))?,
+            );
+            manifest.curves.insert(
+                G2::NAME.to_string(),
+                write_set::<G2>(tau, layout(G2::NAME, g2_count))?,
+            );
+            let path = format!("{}/params.json", set_dir);
+            manifest.save(path.as_str())?;
+            sets.push(SubSet {
+                label: label.to_string(),
+                manifest: path,
+                fingerprint: manifest.fingerprint(),
+            });
+        }
+        let bundle = Self {
+            version: VERSION,
+            tau_g1: encode_point(&(G1::generator() * tau)),
+            tau_g2: encode_point(&(G2::generator() * tau)),
+            shift,
+            shift_g2: encode_point(&(G2::generator() * powers::power(tau, shift))),
+            sets,
+        };
+        bundle.save(path(dir).as_str())?;
+        Ok(bundle)
+    }
+
+    pub fn load(path: &str) -> Result<Self> {
+        let json = std::fs::read_to_string(path).with_context(|| format!("reading {}", path))?;
+        let bundle: Self =
+            serde_json::from_str(json.as_str()).with_context(|| format!("parsing {}", path))?;
+        if bundle.version != VERSION {
+            return Err(anyhow!(
+                "{}: unsupported bundle version {}",
+                path,
+                bundle.version
+            ));
+        }
+        Ok(bundle)
+    }
+
+    pub fn save(&self, path: &str) -> Result<()> {
+        let mut json = serde_json::to_string_pretty(self)?;
+        json.push('\n');
+        std::fs::write(path, json).with_context(|| format!("writing {}", path))
+    }
+
+    /// Checks that the sets of the bundle are the ones it records and share its tau, without
+    /// knowing tau. Reads both sets. Returns the problems found, each prefixed with the label of
+    /// its set and the curve.
+    pub fn check(&self) -> Result<Vec<String>> {
+        let tau_g1 = decode_point::<G1>(self.tau_g1.as_str(), "tau·G1")?;
+        let tau_g2 = decode_point::<G2>(self.tau_g2.as_str(), "tau·G2")?;
+        let shift_g2 = decode_point::<G2>(self.shift_g2.as_str(), "tau^shift·G2")?;
+        let (tau_g1_bytes, tau_g2_bytes) = (hex::decode(&self.tau_g1)?, hex::decode(&self.tau_g2)?);
+        let set = |label: &str| {
+            self.sets
+                .iter()
+                .find(|set| set.label == label)
+                .ok_or_else(|| anyhow!("the bundle has no {} set", label))
+        };
+        let (base, shifted) = (set(BASE)?, set(SHIFTED)?);
+        let mut problems = vec![];
+        let mut manifests = vec![];
+        for set in [base, shifted] {
+            let manifest = Manifest::load(set.manifest.as_str())?;
+            if manifest.fingerprint() != set.fingerprint {
+                problems.push(format!(
+                    "{}: {} doesn't have the fingerprint in the bundle",
+                    set.label, set.manifest
+                ));
+            }
+            for (name, curve) in &manifest.curves {
+                let layout = &curve.layout;
+                let offset = if set.label == BASE { 0 } else { self.shift };
+                if layout.offset != offset
+                    || layout.stride != 1
+                    || layout.reverse
+                    || layout.generator.is_some()
+                {
+                    problems.push(format!(
+                        "{} {}: the set doesn't hold consecutive powers of tau from tau^{}",
+                        set.label, name, offset
+                    ));
+                }
+                problems.extend(
+                    validate::check_hashes(curve)
+                        .into_iter()
+                        .map(|problem| format!("{} {}: {}", set.label, name, problem)),
+                );
+            }
+            manifests.push(manifest);
+        }
+        let curve = |manifest: &Manifest, name: &str| {
+            manifest
+                .curves
+                .get(name)
+                .ok_or_else(|| anyhow!("a set of the bundle has no {} entry", name))
+                .cloned()
+        };
+        let label = |label: &str, name: &str, problems: Vec<Problem>| {
+            problems
+                .into_iter()
+                .map(move |problem| format!("{} {}: {}", label, name, problem))
+                .collect::<Vec<_>>()
+        };
+        let base_g1 = curve(&manifests[0], G1::NAME)?;
+        problems.extend(label(
+            BASE,
+            G1::NAME,
+            ceremony::check_chain(
+                &base_g1,
+                &tau_g2_bytes,
+                &[
+                    (0, G1::generator(), "G"),
+                    (1, tau_g1, "the bundle's tau·G1"),
+                ],
+            ),
+        ));
+        problems.extend(label(
+            BASE,
+            G2::NAME,
+            ceremony::check_chain(
+                &curve(&manifests[0], G2::NAME)?,
+                &tau_g1_bytes,
+                &[
+                    (0, G2::generator(), "G"),
+                    (1, tau_g2, "the bundle's tau·G2"),
+                ],
+            ),
+        ));
+        // The shifted powers must start at point `shift` of the base set, in both groups.
+        let anchor = reader::read_point::<G1>(&base_g1.layout, self.shift)?;
+        let mut shift_g2_bytes = vec![];
+        shift_g2.encode(Encoding::Compressed, &mut shift_g2_bytes);
+        if !G1::check_powers_against(&[G1::generator(), anchor], &shift_g2_bytes)? {
+            problems.push(format!(
+                "the bundle's tau^{}·G2 doesn't match {} G1 point {}",
+                self.shift, BASE, self.shift
+            ));
+        }
+        problems.extend(label(
+            SHIFTED,
+            G1::NAME,
+            ceremony::check_chain(
+                &curve(&manifests[1], G1::NAME)?,
+                &tau_g2_bytes,
+                &[(0, anchor, "the base set's point at the shift")],
+            ),
+        ));
+        problems.extend(label(
+            SHIFTED,
+            G2::NAME,
+            ceremony::check_chain(
+                &curve(&manifests[1], G2::NAME)?,
+                &tau_g1_bytes,
+                &[(0, shift_g2, "the bundle's tau^shift·G2")],
+            ),
+        ));
+        Ok(problems)
+    }
+}
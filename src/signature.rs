@@ -0,0 +1,226 @@
+//! Ed25519 signatures for provenance.
+//!
+//! An authority distributing a set signs its fingerprint (see `Manifest::fingerprint`) and
+//! optionally each chunk hash, so that downloaders holding the authority's public key can check
+//! where the set comes from. Signatures are written as `params.sig.json` next to the manifest by
+//! default. They only vouch for the hashes in the manifest: checking the files against those
+//! hashes is a separate step.
+
+use crate::manifest::Manifest;
+use anyhow::{Context, Result, anyhow};
+use ed25519_dalek::{Signature, Signer, SigningKey, Verifier, VerifyingKey};
+use serde::{Deserialize, Serialize};
+use zeroize::Zeroizing;
+
+pub const VERSION: u32 = 1;
+
+/// Domain separation prefixes of the signed messages.
+const FINGERPRINT_CONTEXT: &[u8] = b"GENERATE_PARAMS_FINGERPRINT_";
+const CHUNK_CONTEXT: &[u8] = b"GENERATE_PARAMS_CHUNK_";
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ChunkSignature {
+    pub curve: String,
+    pub index: u64,
+    pub blake2b: String,
+    /// Hex-encoded signature of the curve name, chunk index, and hash.
+    pub signature: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Signatures {
+    pub version: u32,
+    /// Hex-encoded public key of the signer.
+    pub public_key: String,
+    pub fingerprint: String,
+    /// Hex-encoded signature of the fingerprint.
+    pub signature: String,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub chunks: Vec<ChunkSignature>,
+}
+
+/// Returns the path of the signatures of the set whose manifest is at `manifest_path`, e.g.
+/// `params.sig.json` for `params.json`.
+pub fn path(manifest_path: &str) -> String {
+    let stem = manifest_path.strip_suffix(".json").unwrap_or(manifest_path);
+    format!("{}.sig.json", stem)
+}
+
+/// Reads a signing key from `path`, which must contain the raw 32-byte secret key.
+pub fn load_signing_key(path: &str) -> Result<SigningKey> {
+    let bytes = Zeroizing::new(std::fs::read(path).with_context(|| format!("reading {}", path))?);
+    let bytes: &[u8; 32] = bytes.as_slice().try_into().map_err(|_| {
+        anyhow!(
+            "{} must contain a 32-byte ed25519 secret key, found {} bytes",
+            path,
+            bytes.len()
+        )
+    })?;
+    Ok(SigningKey::from_bytes(bytes))
+}
+
+pub fn parse_public_key(hex: &str) -> Result<VerifyingKey> {
+    let bytes = hex::decode(hex.trim_start_matches("0x"))
+        .map_err(|error| anyhow!("invalid public key hex: {}", error))?;
+    let bytes: [u8; 32] = bytes
+        .try_into()
+        .map_err(|_| anyhow!("ed25519 public keys are 32 bytes long"))?;
+    VerifyingKey::from_bytes(&bytes).map_err(|error| anyhow!("invalid public key: {}", error))
+}
+
+fn fingerprint_message(fingerprint: &str) -> Vec<u8> {
+    [FINGERPRINT_CONTEXT, fingerprint.as_bytes()].concat()
+}
+
+fn chunk_message(curve: &str, index: u64, blake2b: &str) -> Vec<u8> {
+    [
+        CHUNK_CONTEXT,
+        &(curve.len() as u64).to_le_bytes(),
+        curve.as_bytes(),
+        &index.to_le_bytes(),
+        blake2b.as_bytes(),
+    ]
+    .concat()
+}
+
+fn parse_signature(hex: &str) -> Result<Signature> {
+    let bytes = hex::decode(hex).map_err(|error| anyhow!("invalid signature hex: {}", error))?;
+    Signature::from_slice(&bytes).map_err(|error| anyhow!("invalid signature: {}", error))
+}
+
+impl Signatures {
+    /// Signs the fingerprint of `manifest`, and each of its chunk hashes if `chunks` is set.
+    pub fn sign(manifest: &Manifest, key: &SigningKey, chunks: bool) -> Self {
+        let fingerprint = manifest.fingerprint();
+        let signature = key.sign(&fingerprint_message(fingerprint.as_str()));
+        let chunks = if chunks {
+            manifest
+                .curves
+                .iter()
+                .flat_map(|(name, curve)| curve.chunks.iter().map(move |chunk| (name, chunk)))
+                .map(|(name, chunk)| ChunkSignature {
+                    curve: name.clone(),
+                    index: chunk.index,
+                    blake2b: chunk.blake2b.clone(),
+                    signature: hex::encode(
+                        key.sign(&chunk_message(name, chunk.index, chunk.blake2b.as_str()))
+                            .to_bytes(),
+                    ),
+                })
+                .collect()
+        } else {
+            vec![]
+        };
+        Self {
+            version: VERSION,
+            public_key: hex::encode(key.verifying_key().to_bytes()),
+            fingerprint,
+            signature: hex::encode(signature.to_bytes()),
+            chunks,
+        }
+    }
+
+    pub fn load(path: &str) -> Result<Self> {
+        let json = std::fs::read_to_string(path).with_context(|| format!("reading {}", path))?;
+        let signatures: Self =
+            serde_json::from_str(json.as_str()).with_context(|| format!("parsing {}", path))?;
+        if signatures.version != VERSION {
+            return Err(anyhow!(
+                "{}: unsupported signatures version {}",
+                path,
+                signatures.version
+            ));
+        }
+        Ok(signatures)
+    }
+
+    pub fn save(&self, path: &str) -> Result<()> {
+        let mut json = serde_json::to_string_pretty(self)?;
+        json.push('\n');
+        std::fs::write(path, json).with_context(|| format!("writing {}", path))
+    }
+
+    /// Checks that these are signatures by `key` of the fingerprint of `manifest` and of the chunk
+    /// hashes it lists.
+    pub fn verify(&self, manifest: &Manifest, key: &VerifyingKey) -> Result<()> {
+        let fingerprint = manifest.fingerprint();
+        if self.fingerprint != fingerprint {
+            return Err(anyhow!(
+                "the set has fingerprint {} but {} was signed",
+                fingerprint,
+                self.fingerprint
+            ));
+        }
+        key.verify(
+            &fingerprint_message(fingerprint.as_str()),
+            &parse_signature(self.signature.as_str())?,
+        )
+        .map_err(|_| anyhow!("invalid signature of fingerprint {}", fingerprint))?;
+        for chunk in &self.chunks {
+            let entry = manifest
+                .curves
+                .get(&chunk.curve)
+                .and_then(|curve| curve.chunks.iter().find(|entry| entry.index == chunk.index));
+            if entry.map(|entry| entry.blake2b.as_str()) != Some(chunk.blake2b.as_str()) {
+                return Err(anyhow!(
+                    "{} chunk {} doesn't have the signed hash {}",
+                    chunk.curve,
+                    chunk.index,
+                    chunk.blake2b
+                ));
+            }
+            key.verify(
+                &chunk_message(chunk.curve.as_str(), chunk.index, chunk.blake2b.as_str()),
+                &parse_signature(chunk.signature.as_str())?,
+            )
+            .map_err(|_| anyhow!("invalid signature of {} chunk {}", chunk.curve, chunk.index))?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::curve::{G1, Point};
+    use crate::layout::Layout;
+    use blstrs::Scalar;
+
+    #[test]
+    fn signatures_are_verified_and_tampering_is_detected() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut manifest = Manifest::default();
+        let curve = Layout::dense(dir.path(), 10, 4).write_set::<G1>(&Scalar::from(7u64));
+        manifest.curves.insert(G1::NAME.to_string(), curve);
+        let key = SigningKey::from_bytes(&[7; 32]);
+        let signatures = Signatures::sign(&manifest, &key, true);
+        assert_eq!(signatures.chunks.len(), 3);
+        signatures.verify(&manifest, &key.verifying_key()).unwrap();
+
+        let other = SigningKey::from_bytes(&[8; 32]).verifying_key();
+        assert!(signatures.verify(&manifest, &other).is_err());
+
+        let mut tampered = signatures.clone();
+        tampered.fingerprint = Signatures::sign(&Manifest::default(), &key, false).fingerprint;
+        assert!(tampered.verify(&manifest, &key.verifying_key()).is_err());
+
+        let mut tampered = manifest.clone();
+        tampered
+            .curves
+            .get_mut(G1::NAME)
+            .unwrap()
+            .layout
+            .chunk_length = 5;
+        let error = signatures
+            .verify(&tampered, &key.verifying_key())
+            .unwrap_err();
+        assert!(error.to_string().contains("was signed"), "{}", error);
+
+        let mut tampered = signatures;
+        tampered.chunks[1].signature = tampered.chunks[0].signature.clone();
+        let error = tampered
+            .verify(&manifest, &key.verifying_key())
+            .unwrap_err();
+        assert_eq!(error.to_string(), "invalid signature of G1 chunk 1");
+    }
+}
@@ -0,0 +1,130 @@
+//! Statistical smoke test of generated sets.
+//!
+//! Powers of a random tau are indistinguishable from random points, so in their compressed
+//! encodings the sign flag (set when y is the lexicographically largest square root) should be set
+//! about half of the time, the infinity flag never, and the compression flag always. A sample
+//! that's far off is a cheap hint of a systematically broken serialization, e.g. a flag computed
+//! from the wrong coordinate.
+
+use crate::chunk;
+use crate::curve::{Encoding, Point};
+use crate::layout::Layout;
+use anyhow::Result;
+use std::collections::HashSet;
+
+const COMPRESSION_FLAG: u8 = 0x80;
+const INFINITY_FLAG: u8 = 0x40;
+const SIGN_FLAG: u8 = 0x20;
+
+/// Maximum number of chunks read to build a sample.
+const SAMPLED_CHUNKS: u64 = 16;
+
+/// Flag and round-trip tallies over a sample of points.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct Stats {
+    pub samples: u64,
+    pub sign: u64,
+    pub infinity: u64,
+    pub uncompressed: u64,
+    /// Points whose compressed encoding doesn't decode back to them.
+    pub round_trip_failures: u64,
+    pub duplicates: u64,
+}
+
+impl Stats {
+    /// Tallies `points`.
+    pub fn new<P: Point>(points: &[P]) -> Self {
+        let mut stats = Self::default();
+        let mut seen = HashSet::new();
+        for point in points {
+            let mut bytes = vec![];
+            point.encode(Encoding::Compressed, &mut bytes);
+            stats.samples += 1;
+            stats.sign += u64::from(bytes[0] & SIGN_FLAG != 0);
+            stats.infinity += u64::from(bytes[0] & INFINITY_FLAG != 0);
+            stats.uncompressed += u64::from(bytes[0] & COMPRESSION_FLAG == 0);
+            if P::decode(Encoding::Compressed, &bytes).ok().as_ref() != Some(point) {
+                stats.round_trip_failures += 1;
+            }
+            if !seen.insert(bytes) {
+                stats.duplicates += 1;
+            }
+        }
+        stats
+    }
+
+    /// Describes what's wrong with the sample, if anything. The sign flag count is flagged when it's
+    /// more than 5 standard deviations away from half the sample.
+    pub fn anomalies(&self) -> Vec<String> {
+        let mut anomalies = vec![];
+        let deviation = (self.sign as f64 - self.samples as f64 / 2.0).abs();
+        if deviation > 5.0 * (self.samples as f64).sqrt() / 2.0 {
+            anomalies.push(format!(
+                "{} of {} points have the sign flag set",
+                self.sign, self.samples
+            ));
+        }
+        for (count, what) in [
+            (self.infinity, "have the infinity flag set"),
+            (self.uncompressed, "lack the compression flag"),
+            (
+                self.round_trip_failures,
+                "don't survive a compression round trip",
+            ),
+            (self.duplicates, "are duplicates"),
+        ] {
+            if count > 0 {
+                anomalies.push(format!("{} of {} points {}", count, self.samples, what));
+            }
+        }
+        anomalies
+    }
+}
+
+/// Reads a sample of about `samples` points of the `P` set with the given layout, spread over up to
+/// 16 evenly spaced chunks, and tallies it.
+pub fn sample<P: Point>(layout: &Layout, samples: u64) -> Result<Stats> {
+    let chunk_count = layout.chunk_count();
+    let sampled_chunks = chunk_count.min(SAMPLED_CHUNKS);
+    let mut points = vec![];
+    for i in 0..sampled_chunks {
+        let path = layout.chunk_path::<P>(i * chunk_count / sampled_chunks)?;
+        let chunk = chunk::read::<P>(path.as_str())?;
+        let take = samples.div_ceil(sampled_chunks) as usize;
+        points.extend_from_slice(&chunk[..take.min(chunk.len())]);
+    }
+    Ok(Stats::new(&points))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use blstrs::{G1Projective, Scalar};
+
+    #[test]
+    fn stats_of_a_small_set_are_computed() {
+        let dir = tempfile::tempdir().unwrap();
+        let curve = Layout::dense(dir.path(), 64, 8).write_set::<G1Projective>(&Scalar::from(7u64));
+        let stats = sample::<G1Projective>(&curve.layout, 64).unwrap();
+        assert_eq!(stats.samples, 64);
+        assert!(stats.sign > 0 && stats.sign < 64);
+        assert_eq!(stats.anomalies(), Vec::<String>::new());
+    }
+
+    #[test]
+    fn anomalies_are_flagged() {
+        let stats = Stats {
+            samples: 100,
+            sign: 100,
+            duplicates: 3,
+            ..Stats::default()
+        };
+        assert_eq!(
+            stats.anomalies(),
+            [
+                "100 of 100 points have the sign flag set",
+                "3 of 100 points are duplicates"
+            ]
+        );
+    }
+}
@@ -0,0 +1,204 @@
+//! Asynchronous generation API for tokio-based services, enabled by the `async` feature.
+//!
+//! Chunks are computed on tokio's blocking thread pool and written with `tokio::fs`, one at a time
+//! as the returned stream is polled. Cancelling the token ends the stream after the chunk being
+//! written, if any; a chunk whose computation is interrupted is not written.
+//!
+//! A `ChunkHook` can post-process each chunk, e.g. upload it or record its hash in an external
+//! ledger. It's called in chunk order, once the chunk is on disk and before the next one is
+//! computed, with the manifest entry and the bytes of the file.
+
+use crate::chunk;
+use crate::curve::{G1, G2, Point};
+use crate::layout::Layout;
+use crate::manifest::{self, ChunkEntry};
+use crate::powers;
+use anyhow::{Context, Result};
+use blstrs::Scalar;
+use futures::Stream;
+use std::sync::Arc;
+use tokio_util::sync::CancellationToken;
+
+/// Called after each chunk is written, see the module documentation. Returning an error ends the
+/// stream with it. The hook runs on the async task, so slow work should be handed off.
+pub type ChunkHook = Arc<dyn Fn(&ChunkEntry, &[u8]) -> Result<()> + Send + Sync>;
+
+async fn write_chunk<P: Point>(
+    tau: Scalar,
+    layout: &Layout,
+    index: u64,
+    cancel: &CancellationToken,
+    hook: Option<&ChunkHook>,
+) -> Option<Result<ChunkEntry>> {
+    let first = layout.chunk_start(index);
+    let count = layout.chunk_size(index);
+    let (format, version, encoding) = (layout.format, layout.format_version, layout.encoding);
+    let base = match layout.base::<P>() {
+        Ok(base) => base,
+        Err(error) => return Some(Err(error)),
+    };
+    let compute = tokio::task::spawn_blocking(move || {
+        chunk::encode(
+            &powers::compute_from(base, &tau, first, count as usize),
+            format,
+            version,
+            encoding,
+            first,
+        )
+    });
+    let data = tokio::select! {
+        _ = cancel.cancelled() => return None,
+        data = compute => data,
+    };
+    let result = async {
+        let mut data = data??;
+        chunk::pad(&mut data, layout.block_size);
+        let path = layout.chunk_path::<P>(index)?;
+        // Written under a temporary name and renamed, like `chunk::write`.
+        let temporary = format!("{}.tmp", path);
+        tokio::fs::write(temporary.as_str(), &data)
+            .await
+            .with_context(|| format!("writing {}", temporary))?;
+        tokio::fs::rename(temporary.as_str(), path.as_str())
+            .await
+            .with_context(|| format!("renaming {} to {}", temporary, path))?;
+        let entry = ChunkEntry {
+            index,
+            path: path.clone(),
+            count,
+            blake2b: manifest::hash(&data),
+            provenance: None,
+        };
+        if let Some(hook) = hook {
+            hook(&entry, &data).with_context(|| format!("post-processing {}", path))?;
+        }
+        Ok(entry)
+    };
+    Some(result.await)
+}
+
+/// Generates the `P` set described by `layout`, yielding the manifest entry of each chunk once it's
+/// on disk. The stream ends after the last chunk, after the first error, or when `cancel` is
+/// cancelled.
+pub fn generate_async<P: Point>(
+    tau: Scalar,
+    layout: Layout,
+    cancel: CancellationToken,
+) -> impl Stream<Item = Result<ChunkEntry>> {
+    generate_async_with_hook::<P>(tau, layout, cancel, None)
+}
+
+/// Like `generate_async`, but calls `hook`, if any, after writing each chunk. The chunk whose hook
+/// fails stays on disk, and the stream ends with the error of the hook.
+pub fn generate_async_with_hook<P: Point>(
+    tau: Scalar,
+    layout: Layout,
+    cancel: CancellationToken,
+    hook: Option<ChunkHook>,
+) -> impl Stream<Item = Result<ChunkEntry>> {
+    futures::stream::unfold(Some(0u64), move |index| {
+        let layout = layout.clone();
+        let cancel = cancel.clone();
+        let hook = hook.clone();
+        async move {
+            let index = index?;
+            if index >= layout.chunk_count() || cancel.is_cancelled() {
+                return None;
+            }
+            let result = write_chunk::<P>(tau, &layout, index, &cancel, hook.as_ref()).await?;
+            let next = result.is_ok().then_some(index + 1);
+            Some((result, next))
+        }
+    })
+}
+
+pub fn generate_g1_async(
+    tau: Scalar,
+    layout: Layout,
+    cancel: CancellationToken,
+) -> impl Stream<Item = Result<ChunkEntry>> {
+    generate_async::<G1>(tau, layout, cancel)
+}
+
+pub fn generate_g2_async(
+    tau: Scalar,
+    layout: Layout,
+    cancel: CancellationToken,
+) -> impl Stream<Item = Result<ChunkEntry>> {
+    generate_async::<G2>(tau, layout, cancel)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use futures::StreamExt;
+
+    #[tokio::test]
+    async fn stream_writes_every_chunk() {
+        let dir = tempfile::tempdir().unwrap();
+        let layout = Layout::dense(dir.path(), 10, 4);
+        let tau = Scalar::from(7u64);
+        let entries: Vec<_> = generate_g1_async(tau, layout.clone(), CancellationToken::new())
+            .collect()
+            .await;
+        assert_eq!(entries.len(), 3);
+        for (index, entry) in (0..).zip(entries) {
+            let entry = entry.unwrap();
+            assert_eq!(entry.index, index);
+            let points = chunk::read::<G1>(entry.path.as_str()).unwrap();
+            let first = layout.chunk_start(index);
+            assert_eq!(
+                points,
+                powers::compute::<G1>(&tau, first, entry.count as usize)
+            );
+        }
+    }
+
+    #[tokio::test]
+    async fn cancelled_stream_writes_nothing() {
+        let dir = tempfile::tempdir().unwrap();
+        let cancel = CancellationToken::new();
+        cancel.cancel();
+        let layout = Layout::dense(dir.path(), 10, 4);
+        let entries: Vec<_> = generate_g1_async(Scalar::from(7u64), layout, cancel)
+            .collect()
+            .await;
+        assert!(entries.is_empty());
+        assert_eq!(std::fs::read_dir(dir.path()).unwrap().count(), 0);
+    }
+
+    #[tokio::test]
+    async fn failing_hook_stops_the_stream() {
+        let dir = tempfile::tempdir().unwrap();
+        let layout = Layout::dense(dir.path(), 10, 4);
+        let calls = Arc::new(std::sync::Mutex::new(vec![]));
+        let hook: ChunkHook = {
+            let calls = calls.clone();
+            Arc::new(move |entry: &ChunkEntry, data: &[u8]| {
+                assert_eq!(manifest::hash(data), entry.blake2b);
+                calls.lock().unwrap().push(entry.index);
+                if entry.index == 1 {
+                    return Err(anyhow::anyhow!("upload failed"));
+                }
+                Ok(())
+            })
+        };
+        let entries: Vec<_> = generate_async_with_hook::<G1>(
+            Scalar::from(7u64),
+            layout.clone(),
+            CancellationToken::new(),
+            Some(hook),
+        )
+        .collect()
+        .await;
+        assert_eq!(entries.len(), 2);
+        assert!(entries[0].is_ok());
+        let error = entries[1].as_ref().unwrap_err();
+        assert!(format!("{:#}", error).ends_with("upload failed"));
+        assert_eq!(*calls.lock().unwrap(), [0, 1]);
+        // The chunk whose hook failed stays on disk, the next one isn't computed.
+        let path = |index| layout.chunk_path::<G1>(index).unwrap();
+        assert!(std::path::Path::new(&path(1)).exists());
+        assert!(!std::path::Path::new(&path(2)).exists());
+    }
+}
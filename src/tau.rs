@@ -0,0 +1,218 @@
+use anyhow::{Result, anyhow};
+use blstrs::Scalar;
+use dusk_bls12_381::BlsScalar as DuskScalar;
+use ff::Field;
+use rand_chacha::ChaCha20Rng;
+use rand_chacha::rand_core::{RngCore, SeedableRng};
+use std::sync::atomic::{self, Ordering};
+use zeroize::{Zeroize, ZeroizeOnDrop, Zeroizing};
+
+/// Length of the ChaCha20 seed.
+pub const SEED_LENGTH: usize = 32;
+
+/// Byte order of scalars written as bytes.
+///
+/// Scalars read or written as text are big-endian by default, which is how blstrs displays them
+/// and how they read as numbers.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum Endianness {
+    Big,
+    Little,
+}
+
+/// A secret scalar, i.e. tau, zeroed when dropped.
+///
+/// This is best-effort: `Scalar` is `Copy`, so temporary copies made during computations are not
+/// cleared.
+pub struct SecretScalar(Scalar);
+
+impl SecretScalar {
+    pub fn new(scalar: Scalar) -> Self {
+        Self(scalar)
+    }
+}
+
+impl std::ops::Deref for SecretScalar {
+    type Target = Scalar;
+
+    fn deref(&self) -> &Scalar {
+        &self.0
+    }
+}
+
+impl std::fmt::Debug for SecretScalar {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("SecretScalar(..)")
+    }
+}
+
+impl Zeroize for SecretScalar {
+    fn zeroize(&mut self) {
+        // SAFETY: `self.0` is a valid, aligned, exclusively borrowed `Scalar`.
+        unsafe { std::ptr::write_volatile(&mut self.0, Scalar::ZERO) };
+        atomic::compiler_fence(Ordering::SeqCst);
+    }
+}
+
+impl Drop for SecretScalar {
+    fn drop(&mut self) {
+        self.zeroize();
+    }
+}
+
+impl ZeroizeOnDrop for SecretScalar {}
+
+/// Reduces 64 little-endian bytes modulo the group order. The round trip through little-endian
+/// bytes is internal: both dusk and blstrs use that order for their byte conversions.
+pub(crate) fn scalar_from_wide(bytes: &[u8; 64]) -> SecretScalar {
+    let mut scalar = DuskScalar::from_bytes_wide(bytes);
+    let bytes = Zeroizing::new(scalar.to_bytes());
+    scalar.zeroize();
+    SecretScalar::new(Scalar::from_bytes_le(&bytes).into_option().unwrap())
+}
+
+/// Samples a uniformly random scalar from system randomness, which fails rather than panicking
+/// where there's none, e.g. in minimal containers without `/dev/urandom` or the `getrandom`
+/// syscall.
+pub fn get_random_scalar() -> Result<SecretScalar> {
+    random_scalar_from(getrandom::fill)
+}
+
+/// Source of system randomness, `getrandom::fill` outside of tests.
+type Source = fn(&mut [u8]) -> Result<(), getrandom::Error>;
+
+fn fill_random(source: Source, bytes: &mut [u8]) -> Result<()> {
+    source(bytes).map_err(|error| anyhow!("no system randomness available: {}", error))
+}
+
+fn random_scalar_from(source: Source) -> Result<SecretScalar> {
+    let mut bytes = Zeroizing::new([0u8; 64]);
+    fill_random(source, bytes.as_mut())?;
+    Ok(scalar_from_wide(&bytes))
+}
+
+/// Returns a fresh seed for the random weights of batched checks, or an error if there's no system
+/// randomness, like `get_random_scalar`.
+pub fn random_seed() -> Result<[u8; SEED_LENGTH]> {
+    let mut seed = [0u8; SEED_LENGTH];
+    fill_random(getrandom::fill, &mut seed)?;
+    Ok(seed)
+}
+
+/// Normalizes a user-provided seed to the `SEED_LENGTH` bytes required by ChaCha20.
+///
+/// Seeds of exactly `SEED_LENGTH` bytes are used as they are. Any other non-empty seed is replaced
+/// by its 32-byte BLAKE2b hash, so that shorter seeds are never zero-padded and longer ones never
+/// silently truncated.
+pub fn normalize_seed(seed: &[u8]) -> Result<[u8; SEED_LENGTH]> {
+    if seed.is_empty() {
+        return Err(anyhow!("the seed must not be empty"));
+    }
+    if let Ok(seed) = seed.try_into() {
+        return Ok(seed);
+    }
+    let hash = blake2b_simd::Params::new()
+        .hash_length(SEED_LENGTH)
+        .hash(seed);
+    Ok(hash.as_bytes().try_into().unwrap())
+}
+
+/// Deterministically derives tau from a seed of any length (see `normalize_seed`).
+pub fn scalar_from_seed(seed: &[u8]) -> Result<SecretScalar> {
+    let mut rng = ChaCha20Rng::from_seed(*Zeroizing::new(normalize_seed(seed)?));
+    let mut bytes = Zeroizing::new([0u8; 64]);
+    rng.fill_bytes(bytes.as_mut());
+    Ok(scalar_from_wide(&bytes))
+}
+
+/// Parses a scalar written as 32 bytes in hex with an optional `0x` prefix, in the given byte
+/// order. Non-canonical values (not less than the group order) are rejected.
+pub fn parse_scalar_hex(hex: &str, endianness: Endianness) -> Result<SecretScalar> {
+    let bytes = Zeroizing::new(
+        hex::decode(hex.trim_start_matches("0x"))
+            .map_err(|error| anyhow!("invalid scalar hex: {}", error))?,
+    );
+    let bytes: &[u8; 32] = bytes
+        .as_slice()
+        .try_into()
+        .map_err(|_| anyhow!("a scalar must be exactly 32 bytes long"))?;
+    match endianness {
+        Endianness::Big => Scalar::from_bytes_be(bytes),
+        Endianness::Little => Scalar::from_bytes_le(bytes),
+    }
+    .into_option()
+    .map(SecretScalar::new)
+    .ok_or_else(|| anyhow!("the scalar is not less than the group order"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn seeds_of_the_seed_length_are_used_as_they_are() {
+        let seed = [7u8; SEED_LENGTH];
+        assert_eq!(normalize_seed(&seed).unwrap(), seed);
+    }
+
+    #[test]
+    fn other_seeds_are_hashed() {
+        let short = normalize_seed(b"short").unwrap();
+        let padded = normalize_seed(&[b"short".as_slice(), &[0; 27]].concat()).unwrap();
+        assert_ne!(short, padded);
+        assert_ne!(normalize_seed(&[7u8; 64]).unwrap(), [7u8; SEED_LENGTH]);
+        assert!(normalize_seed(b"").is_err());
+    }
+
+    #[test]
+    fn scalars_are_parsed_in_both_byte_orders() {
+        let mut bytes = [0u8; 32];
+        bytes[..2].copy_from_slice(&[1, 2]);
+        let little = hex::encode(bytes);
+        bytes.reverse();
+        let big = format!("0x{}", hex::encode(bytes));
+        let expected = Scalar::from(0x0201u64);
+        assert_eq!(*parse_scalar_hex(&big, Endianness::Big).unwrap(), expected);
+        assert_eq!(
+            *parse_scalar_hex(&little, Endianness::Little).unwrap(),
+            expected
+        );
+        assert_ne!(
+            *parse_scalar_hex(&little, Endianness::Big).unwrap(),
+            expected
+        );
+        // 0x80 is a small value as the last byte of a big-endian scalar, but beyond the group
+        // order as the last byte of a little-endian one.
+        let high = format!("{}80", "00".repeat(31));
+        assert!(parse_scalar_hex(&high, Endianness::Big).is_ok());
+        assert!(parse_scalar_hex(&high, Endianness::Little).is_err());
+        assert!(parse_scalar_hex("0102", Endianness::Big).is_err());
+    }
+
+    #[test]
+    fn missing_system_randomness_is_an_error() {
+        fn unsupported(_: &mut [u8]) -> Result<(), getrandom::Error> {
+            Err(getrandom::Error::UNSUPPORTED)
+        }
+        let error = random_scalar_from(unsupported).unwrap_err();
+        assert!(
+            error
+                .to_string()
+                .starts_with("no system randomness available: ")
+        );
+        assert!(random_scalar_from(getrandom::fill).is_ok());
+    }
+
+    #[test]
+    fn secret_scalars_are_zeroed_when_dropped() {
+        let mut secret = std::mem::ManuallyDrop::new(SecretScalar::new(Scalar::from(7u64)));
+        assert_eq!(**secret, Scalar::from(7u64));
+        // SAFETY: `secret` isn't used as a `SecretScalar` after the drop, only its memory, which
+        // `ManuallyDrop` keeps in place, is read.
+        let tau = unsafe {
+            std::mem::ManuallyDrop::drop(&mut secret);
+            std::ptr::read_volatile(&secret.0)
+        };
+        assert_eq!(tau, Scalar::ZERO);
+    }
+}
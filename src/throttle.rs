@@ -0,0 +1,114 @@
+//! Compute-side rate limiting, to keep laptops and shared machines under a thermal or power budget.
+//!
+//! A token bucket refilled at the target rate, shared by the generator threads so that the cap
+//! applies to the total rate. The bucket holds at most 100 ms worth of points, which bounds bursts
+//! after a stall. A thread that takes more tokens than available puts the bucket in debt and sleeps
+//! just long enough to pay it back, so sleeps stay short and the sustained rate converges to the
+//! cap.
+
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+#[derive(Debug)]
+struct Bucket {
+    tokens: f64,
+    refilled: Instant,
+}
+
+#[derive(Debug)]
+pub struct RateLimiter {
+    points_per_second: u64,
+    capacity: f64,
+    bucket: Mutex<Bucket>,
+}
+
+impl RateLimiter {
+    pub fn new(points_per_second: u64) -> Self {
+        let capacity = (points_per_second as f64 / 10.0).max(1.0);
+        Self {
+            points_per_second,
+            capacity,
+            bucket: Mutex::new(Bucket {
+                tokens: capacity,
+                refilled: Instant::now(),
+            }),
+        }
+    }
+
+    pub fn points_per_second(&self) -> u64 {
+        self.points_per_second
+    }
+
+    /// Takes a token for one point, sleeping first if the cap has been reached.
+    pub fn acquire(&self) {
+        let wait = self.take(Instant::now());
+        if !wait.is_zero() {
+            std::thread::sleep(wait);
+        }
+    }
+
+    /// Takes a token for one point at time `now`, returning how long to sleep to pay back the debt
+    /// of the bucket, if any.
+    fn take(&self, now: Instant) -> Duration {
+        let mut bucket = self.bucket.lock().unwrap();
+        let elapsed = now.saturating_duration_since(bucket.refilled);
+        let refill = elapsed.as_secs_f64() * self.points_per_second as f64;
+        bucket.tokens = (bucket.tokens + refill).min(self.capacity) - 1.0;
+        bucket.refilled = bucket.refilled.max(now);
+        if bucket.tokens >= 0.0 {
+            return Duration::ZERO;
+        }
+        Duration::from_secs_f64(-bucket.tokens / self.points_per_second as f64)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Takes `count` points from `limiter` as threads would, with the simulated clock `now`
+    /// advancing by each sleep.
+    fn take(limiter: &RateLimiter, now: &mut Instant, count: usize) {
+        for _ in 0..count {
+            *now += limiter.take(*now);
+        }
+    }
+
+    #[test]
+    fn sustained_rate_is_the_cap() {
+        let limiter = RateLimiter::new(1000);
+        let mut now = limiter.bucket.lock().unwrap().refilled;
+        let start = now;
+        // The initial burst of 100 points is free.
+        take(&limiter, &mut now, 100);
+        assert_eq!(now, start);
+        take(&limiter, &mut now, 500);
+        let elapsed = (now - start).as_secs_f64();
+        assert!((0.499..0.501).contains(&elapsed), "{}", elapsed);
+
+        // Idling refills the bucket up to its capacity only.
+        now += Duration::from_secs(10);
+        let idle = now;
+        take(&limiter, &mut now, 100);
+        assert_eq!(now, idle);
+        take(&limiter, &mut now, 1);
+        assert!(now > idle);
+    }
+
+    #[test]
+    fn threads_sleep_to_stay_under_the_cap() {
+        let limiter = RateLimiter::new(1000);
+        let start = Instant::now();
+        std::thread::scope(|scope| {
+            for _ in 0..2 {
+                scope.spawn(|| {
+                    for _ in 0..100 {
+                        limiter.acquire();
+                    }
+                });
+            }
+        });
+        // 100 points past the burst take 100 ms at least, give or take rounding.
+        assert!(start.elapsed() >= Duration::from_millis(90));
+    }
+}
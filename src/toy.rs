@@ -0,0 +1,274 @@
+//! Toy groups for fast end-to-end runs in CI, enabled by the test-only `toy` feature.
+//!
+//! A toy point is the additive group of the BLS12-381 scalar field, i.e. the point "x·G" is
+//! represented by x itself. It's a prime-order group of the same order as G1 and G2, so it
+//! implements the same traits as the real groups and flows through generation, chunking, resuming,
+//! validation, and reading unchanged, but group operations are scalar field operations, about a
+//! thousand times faster than curve arithmetic. Discrete logarithms are trivial, so toy sets are
+//! worthless as parameters, and their compressed encodings have none of the flags `--stats`
+//! checks.
+//!
+//! Points are encoded as the 32-byte little-endian scalar, twice in the uncompressed encoding with
+//! the second copy negated, so that encodings of different lengths differ in content too.
+
+use crate::curve::Point;
+use blstrs::Scalar;
+use ff::{Field, PrimeField};
+use group::prime::{PrimeCurve, PrimeCurveAffine, PrimeGroup};
+use group::{Curve, Group, GroupEncoding, UncompressedEncoding};
+use rand_core::RngCore;
+use std::iter::Sum;
+use std::ops::{Add, AddAssign, Mul, MulAssign, Neg, Sub, SubAssign};
+use subtle::{Choice, ConstantTimeEq, CtOption};
+
+/// Point of a toy group. `ID` tells the G1 and G2 stand-ins apart.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Toy<const ID: u8>(Scalar);
+
+/// Stand-in for `G1Projective`.
+pub type ToyG1 = Toy<1>;
+
+/// Stand-in for `G2Projective`.
+pub type ToyG2 = Toy<2>;
+
+#[derive(Debug, Clone, Copy)]
+pub struct Uncompressed([u8; 64]);
+
+impl Default for Uncompressed {
+    fn default() -> Self {
+        Self([0; 64])
+    }
+}
+
+impl AsRef<[u8]> for Uncompressed {
+    fn as_ref(&self) -> &[u8] {
+        &self.0
+    }
+}
+
+impl AsMut<[u8]> for Uncompressed {
+    fn as_mut(&mut self) -> &mut [u8] {
+        &mut self.0
+    }
+}
+
+impl<const ID: u8> Neg for Toy<ID> {
+    type Output = Self;
+
+    fn neg(self) -> Self {
+        Self(-self.0)
+    }
+}
+
+/// Implements a binary operator and its assigning form for all combinations of owned and borrowed
+/// operands.
+macro_rules! impl_op {
+    ($trait:ident, $method:ident, $assign_trait:ident, $assign_method:ident, $rhs:ty, |$a:ident, $b:ident| $body:expr) => {
+        impl<const ID: u8> $trait<$rhs> for Toy<ID> {
+            type Output = Self;
+
+            fn $method(self, $b: $rhs) -> Self {
+                let $a = self;
+                $body
+            }
+        }
+
+        impl<'r, const ID: u8> $trait<&'r $rhs> for Toy<ID> {
+            type Output = Self;
+
+            fn $method(self, $b: &'r $rhs) -> Self {
+                let $a = self;
+                let $b = *$b;
+                $body
+            }
+        }
+
+        impl<const ID: u8> $assign_trait<$rhs> for Toy<ID> {
+            fn $assign_method(&mut self, $b: $rhs) {
+                let $a = *self;
+                *self = $body;
+            }
+        }
+
+        impl<'r, const ID: u8> $assign_trait<&'r $rhs> for Toy<ID> {
+            fn $assign_method(&mut self, $b: &'r $rhs) {
+                let $a = *self;
+                let $b = *$b;
+                *self = $body;
+            }
+        }
+    };
+}
+
+impl_op!(Add, add, AddAssign, add_assign, Toy<ID>, |a, b| Toy(
+    a.0 + b.0
+));
+impl_op!(Sub, sub, SubAssign, sub_assign, Toy<ID>, |a, b| Toy(
+    a.0 - b.0
+));
+impl_op!(Mul, mul, MulAssign, mul_assign, Scalar, |a, b| Toy(a.0 * b));
+
+impl<const ID: u8> Sum for Toy<ID> {
+    fn sum<I: Iterator<Item = Self>>(iter: I) -> Self {
+        iter.fold(<Self as Group>::identity(), |sum, point| sum + point)
+    }
+}
+
+impl<'a, const ID: u8> Sum<&'a Self> for Toy<ID> {
+    fn sum<I: Iterator<Item = &'a Self>>(iter: I) -> Self {
+        iter.copied().sum()
+    }
+}
+
+impl<const ID: u8> Group for Toy<ID> {
+    type Scalar = Scalar;
+
+    fn random(rng: impl RngCore) -> Self {
+        Self(Scalar::random(rng))
+    }
+
+    fn identity() -> Self {
+        Self(Scalar::ZERO)
+    }
+
+    fn generator() -> Self {
+        Self(Scalar::ONE)
+    }
+
+    fn is_identity(&self) -> Choice {
+        self.0.ct_eq(&Scalar::ZERO)
+    }
+
+    fn double(&self) -> Self {
+        Self(self.0.double())
+    }
+}
+
+impl<const ID: u8> GroupEncoding for Toy<ID> {
+    type Repr = [u8; 32];
+
+    fn from_bytes(bytes: &Self::Repr) -> CtOption<Self> {
+        Scalar::from_repr(*bytes).map(Self)
+    }
+
+    fn from_bytes_unchecked(bytes: &Self::Repr) -> CtOption<Self> {
+        Self::from_bytes(bytes)
+    }
+
+    fn to_bytes(&self) -> Self::Repr {
+        self.0.to_repr()
+    }
+}
+
+impl<const ID: u8> UncompressedEncoding for Toy<ID> {
+    type Uncompressed = Uncompressed;
+
+    fn from_uncompressed(bytes: &Uncompressed) -> CtOption<Self> {
+        let value = Scalar::from_repr(bytes.0[..32].try_into().unwrap());
+        let negated = Scalar::from_repr(bytes.0[32..].try_into().unwrap());
+        value.and_then(|value| {
+            negated.and_then(|negated| CtOption::new(Self(value), (value + negated).is_zero()))
+        })
+    }
+
+    fn from_uncompressed_unchecked(bytes: &Uncompressed) -> CtOption<Self> {
+        Self::from_uncompressed(bytes)
+    }
+
+    fn to_uncompressed(&self) -> Uncompressed {
+        let mut bytes = Uncompressed::default();
+        bytes.0[..32].copy_from_slice(&self.0.to_repr());
+        bytes.0[32..].copy_from_slice(&(-self.0).to_repr());
+        bytes
+    }
+}
+
+impl<const ID: u8> PrimeGroup for Toy<ID> {}
+
+/// Toy points are their own affine representation.
+impl<const ID: u8> Curve for Toy<ID> {
+    type AffineRepr = Self;
+
+    fn to_affine(&self) -> Self {
+        *self
+    }
+}
+
+impl<const ID: u8> PrimeCurve for Toy<ID> {
+    type Affine = Self;
+}
+
+impl<const ID: u8> PrimeCurveAffine for Toy<ID> {
+    type Scalar = Scalar;
+    type Curve = Self;
+
+    fn identity() -> Self {
+        <Self as Group>::identity()
+    }
+
+    fn generator() -> Self {
+        <Self as Group>::generator()
+    }
+
+    fn is_identity(&self) -> Choice {
+        <Self as Group>::is_identity(self)
+    }
+
+    fn to_curve(&self) -> Self {
+        *self
+    }
+}
+
+impl<const ID: u8> Point for Toy<ID> {
+    const NAME: &'static str = if ID == 1 { "G1" } else { "G2" };
+    const SCRATCH_SIZE: usize = 32;
+
+    fn write_scratch(&self, out: &mut Vec<u8>) {
+        out.extend_from_slice(&self.0.to_repr());
+    }
+
+    fn read_scratch(bytes: &[u8]) -> anyhow::Result<Self> {
+        Self::from_bytes(bytes.try_into()?)
+            .into_option()
+            .ok_or_else(|| anyhow::anyhow!("invalid toy scratch point"))
+    }
+
+    fn write_projective(&self, out: &mut Vec<u8>) {
+        self.write_scratch(out);
+    }
+
+    fn read_projective(bytes: &[u8]) -> Option<Self> {
+        Self::read_scratch(bytes).ok()
+    }
+
+    /// Toy points have no limbs, so this is the uncompressed encoding, the size of which
+    /// `encoded_size` reports for both.
+    fn write_limbs(&self, out: &mut Vec<u8>) {
+        out.extend_from_slice(self.to_uncompressed().as_ref());
+    }
+
+    fn read_limbs(bytes: &[u8]) -> Option<Self> {
+        Self::from_uncompressed(&Uncompressed(bytes.try_into().ok()?)).into_option()
+    }
+
+    fn check_powers(points: &[Self], tau: &Scalar) -> anyhow::Result<bool> {
+        Ok(points.windows(2).all(|pair| pair[1].0 == pair[0].0 * tau))
+    }
+
+    /// Toy points of both groups share their encoding, and tau·G is tau itself.
+    fn check_powers_against(points: &[Self], tau_other: &[u8]) -> anyhow::Result<bool> {
+        let tau = Self::decode(crate::curve::Encoding::Compressed, tau_other)?;
+        Self::check_powers(points, &tau.0)
+    }
+
+    fn hash_to_curve(message: &[u8], dst: &[u8]) -> Self {
+        let hash = blake2b_simd::Params::new()
+            .hash_length(64)
+            .to_state()
+            .update(&(dst.len() as u64).to_le_bytes())
+            .update(dst)
+            .update(message)
+            .finalize();
+        Self(*crate::tau::scalar_from_wide(hash.as_array()))
+    }
+}
@@ -0,0 +1,329 @@
+//! Structural validation of generated sets, and full verification of their content.
+
+use crate::chunk::{self, Header};
+use crate::curve::Point;
+use crate::layout::Layout;
+use crate::manifest::{self, CurveManifest};
+use crate::powers;
+use anyhow::{Context, Result, anyhow};
+use blstrs::Scalar;
+use serde::{Deserialize, Serialize};
+
+/// A problem found in a set, attributed to the chunk that needs to be repaired if any.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Problem {
+    pub chunk: Option<u64>,
+    pub message: String,
+}
+
+impl Problem {
+    fn new(chunk: u64, message: String) -> Self {
+        Self {
+            chunk: Some(chunk),
+            message,
+        }
+    }
+}
+
+impl std::fmt::Display for Problem {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.message.as_str())
+    }
+}
+
+/// Checks the chunk headers of the `P` set described by `curve`: chunk K must exist, contain `P`
+/// points, start at global index K·chunk_length, and pick up exactly where chunk K-1 ends, with the
+/// expected number of points, and have the planned file size. This catches filename/index
+/// mismatches, e.g. from sharding mistakes. Returns the problems found.
+pub fn check_indices<P: Point>(curve: &CurveManifest) -> Vec<Problem> {
+    let mut problems = vec![];
+    let layout = &curve.layout;
+    let chunk_count = layout.chunk_count();
+    let mut previous: Option<Header> = None;
+    for index in 0..chunk_count {
+        let (planned, header) = match layout.plan_chunk::<P>(index).and_then(|planned| {
+            let header = chunk::read_file_header(planned.path.as_str())?;
+            Ok((planned, header))
+        }) {
+            Ok(chunk) => chunk,
+            Err(error) => {
+                problems.push(Problem::new(index, format!("chunk {}: {:#}", index, error)));
+                previous = None;
+                continue;
+            }
+        };
+        let path = planned.path.as_str();
+        if header.curve != P::NAME {
+            problems.push(Problem::new(
+                index,
+                format!(
+                    "chunk {} ({}) contains {} points, expected {}",
+                    index,
+                    path,
+                    header.curve,
+                    P::NAME
+                ),
+            ));
+        }
+        if header.first_index != planned.first_index {
+            problems.push(Problem::new(
+                index,
+                format!(
+                    "chunk {} ({}) claims start {} but expected {}",
+                    index, path, header.first_index, planned.first_index
+                ),
+            ));
+        }
+        if header.count != planned.count {
+            problems.push(Problem::new(
+                index,
+                format!(
+                    "chunk {} ({}) has {} points but expected {}",
+                    index, path, header.count, planned.count
+                ),
+            ));
+        }
+        match std::fs::metadata(path) {
+            Ok(metadata) if metadata.len() != planned.byte_size => problems.push(Problem::new(
+                index,
+                format!(
+                    "chunk {} ({}) is {} bytes long but expected {}",
+                    index,
+                    path,
+                    metadata.len(),
+                    planned.byte_size
+                ),
+            )),
+            Ok(_) => {}
+            Err(error) => problems.push(Problem::new(
+                index,
+                format!("chunk {} ({}): {}", index, path, error),
+            )),
+        }
+        if let Some(previous) = &previous {
+            let previous_end = previous.first_index + previous.count;
+            if header.first_index > previous_end {
+                problems.push(Problem::new(
+                    index,
+                    format!(
+                        "gap of {} points between chunk {} and chunk {}",
+                        header.first_index - previous_end,
+                        index - 1,
+                        index
+                    ),
+                ));
+            } else if header.first_index < previous_end {
+                problems.push(Problem::new(
+                    index,
+                    format!(
+                        "chunk {} overlaps chunk {} by {} points",
+                        index,
+                        index - 1,
+                        previous_end - header.first_index
+                    ),
+                ));
+            }
+        }
+        previous = Some(header);
+    }
+    // The extra chunk would have a full `count`, which isn't knowable from the layout, so patterns
+    // that use `{count}` aren't checked.
+    let extra = Layout {
+        count: layout.chunk_start(chunk_count + 1),
+        ..layout.clone()
+    }
+    .chunk_path::<P>(chunk_count);
+    if let Ok(extra) = extra
+        && std::path::Path::new(extra.as_str()).exists()
+    {
+        problems.push(Problem {
+            chunk: None,
+            message: format!(
+                "unexpected chunk {} ({}) beyond the end of the set",
+                chunk_count, extra
+            ),
+        });
+    }
+    problems
+}
+
+/// Checks the hash of each chunk file of `curve` against the manifest, which catches corrupted
+/// points that the headers and file sizes don't reveal. Reads the whole set.
+pub fn check_hashes(curve: &CurveManifest) -> Vec<Problem> {
+    let mut problems = vec![];
+    for entry in &curve.chunks {
+        let path = entry.path.as_str();
+        match std::fs::read(path) {
+            Ok(data) if manifest::hash(&data) != entry.blake2b => problems.push(Problem::new(
+                entry.index,
+                format!(
+                    "chunk {} ({}) doesn't have the hash in the manifest",
+                    entry.index, path
+                ),
+            )),
+            Ok(_) => {}
+            Err(error) => problems.push(Problem::new(
+                entry.index,
+                format!("chunk {} ({}): {}", entry.index, path, error),
+            )),
+        }
+    }
+    problems
+}
+
+/// Checks that all the points of the `P` set described by `curve` decode to valid group elements,
+/// i.e. points of the curve in the prime-order subgroup. The subgroup checks make this much slower
+/// than `check_hashes`.
+pub fn check_points<P: Point>(curve: &CurveManifest) -> Vec<Problem> {
+    let mut problems = vec![];
+    for entry in &curve.chunks {
+        if let Err(error) = chunk::read::<P>(entry.path.as_str()) {
+            problems.push(Problem::new(
+                entry.index,
+                format!("chunk {}: {:#}", entry.index, error),
+            ));
+        }
+    }
+    problems
+}
+
+pub const MARKER_VERSION: u32 = 1;
+
+/// Note that the points of a set passed `check_points`, so that later validations can check the
+/// hashes only. It records the fingerprint of the set, which covers the hashes of all the chunk
+/// files, so it no longer applies once any of them changes.
+///
+/// The marker isn't authenticated: anyone who can write it can make validations skip the subgroup
+/// checks, so it must be kept where only trusted parties can write, like the set itself.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Marker {
+    pub version: u32,
+    pub fingerprint: String,
+}
+
+impl Marker {
+    /// Reads the marker at `path`, if there's one.
+    pub fn load(path: &str) -> Result<Option<Self>> {
+        let json = match std::fs::read_to_string(path) {
+            Ok(json) => json,
+            Err(error) if error.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+            Err(error) => return Err(error).with_context(|| format!("reading {}", path)),
+        };
+        let marker: Self =
+            serde_json::from_str(json.as_str()).with_context(|| format!("parsing {}", path))?;
+        if marker.version != MARKER_VERSION {
+            return Err(anyhow!(
+                "{}: unsupported marker version {}",
+                path,
+                marker.version
+            ));
+        }
+        Ok(Some(marker))
+    }
+
+    pub fn save(&self, path: &str) -> Result<()> {
+        let mut json = serde_json::to_string_pretty(self)?;
+        json.push('\n');
+        std::fs::write(path, json).with_context(|| format!("writing {}", path))
+    }
+}
+
+/// Checks the content of the `P` set described by `curve` against the tau it was generated with:
+/// each chunk file must have its manifest hash, start with the expected power of tau, and hold
+/// consecutive powers, as checked with pairings by `Point::check_powers`. This reads the whole set
+/// and costs about as much as generating it, but catches corruption that the structure and hashes
+/// can't, e.g. a point miscomputed by faulty hardware before it was hashed. Returns one message per
+/// problem found, or an error if there's no system randomness for the pairing checks.
+pub fn check_powers<P: Point>(curve: &CurveManifest, tau: &Scalar) -> Result<Vec<Problem>> {
+    let mut problems = vec![];
+    let layout = &curve.layout;
+    let step = layout.step(tau);
+    let base = match layout.base::<P>() {
+        Ok(base) => base,
+        Err(error) => {
+            return Ok(vec![Problem {
+                chunk: None,
+                message: format!("{:#}", error),
+            }]);
+        }
+    };
+    for entry in &curve.chunks {
+        let path = entry.path.as_str();
+        match std::fs::read(path) {
+            Ok(data) if manifest::hash(&data) != entry.blake2b => problems.push(Problem::new(
+                entry.index,
+                format!(
+                    "chunk {} ({}) doesn't have the hash in the manifest",
+                    entry.index, path
+                ),
+            )),
+            Ok(_) => {}
+            Err(error) => {
+                problems.push(Problem::new(
+                    entry.index,
+                    format!("chunk {} ({}): {}", entry.index, path, error),
+                ));
+                continue;
+            }
+        }
+        let points = match chunk::read::<P>(path) {
+            Ok(points) => points,
+            Err(error) => {
+                problems.push(Problem::new(
+                    entry.index,
+                    format!("chunk {}: {:#}", entry.index, error),
+                ));
+                continue;
+            }
+        };
+        let first = layout.power(layout.chunk_start(entry.index));
+        if points.first() != powers::compute_from(base, tau, first, 1).first() {
+            problems.push(Problem::new(
+                entry.index,
+                format!(
+                    "chunk {} ({}) doesn't start with tau^{}·G",
+                    entry.index, path, first
+                ),
+            ));
+        }
+        if !P::check_powers(&points, &step)? {
+            problems.push(Problem::new(
+                entry.index,
+                format!(
+                    "chunk {} ({}) doesn't hold consecutive powers of tau",
+                    entry.index, path
+                ),
+            ));
+        }
+    }
+    Ok(problems)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::curve::G1;
+
+    #[test]
+    fn misindexed_chunks_are_reported() {
+        let dir = tempfile::tempdir().unwrap();
+        let tau = Scalar::from(7u64);
+        let curve = Layout::dense(dir.path(), 12, 4).write_set::<G1>(&tau);
+        assert_eq!(check_indices::<G1>(&curve), vec![]);
+
+        curve.layout.write_chunk_at::<G1>(&tau, 1, 8);
+        let messages: Vec<_> = check_indices::<G1>(&curve)
+            .into_iter()
+            .map(|problem| problem.message)
+            .collect();
+        let path = curve.layout.chunk_path::<G1>(1).unwrap();
+        assert_eq!(
+            messages,
+            [
+                format!("chunk 1 ({}) claims start 8 but expected 4", path),
+                "gap of 4 points between chunk 0 and chunk 1".to_string(),
+                "chunk 2 overlaps chunk 1 by 4 points".to_string(),
+            ]
+        );
+    }
+}
@@ -0,0 +1,106 @@
+//! KZG verifier keys extracted from full sets.
+//!
+//! Provers need all the G1 powers of a set, but KZG verifiers only need the G1 generator, the G2
+//! generator, and tau·G2, i.e. G1 point 0 and G2 points 0 and 1 of a set holding consecutive
+//! powers of tau times the standard generators. A verifier key file is 248 bytes long: the magic
+//! bytes "GPKZGVK1", then these three points with the standard compressed encoding, 48, 96, and 96
+//! bytes long.
+
+use crate::curve::{Encoding, G1, G2, Point};
+use crate::manifest::Manifest;
+use crate::reader;
+use anyhow::{Context, Result, anyhow};
+use group::Group;
+
+const MAGIC: [u8; 8] = *b"GPKZGVK1";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct VerifierKey {
+    pub g1: G1,
+    pub g2: G2,
+    pub tau_g2: G2,
+}
+
+impl VerifierKey {
+    /// Reads the verifier key of the set described by `manifest` from its chunk files.
+    pub fn extract(manifest: &Manifest) -> Result<Self> {
+        let layout = |name: &str, count: u64| {
+            let layout = &manifest
+                .curves
+                .get(name)
+                .ok_or_else(|| anyhow!("the manifest has no {} entry", name))?
+                .layout;
+            if !layout.is_plain() {
+                return Err(anyhow!(
+                    "the {} set doesn't hold consecutive powers of tau times the standard \
+                     generator",
+                    name
+                ));
+            }
+            if layout.count < count {
+                return Err(anyhow!(
+                    "the {} set has {} points, {} are needed",
+                    name,
+                    layout.count,
+                    count
+                ));
+            }
+            Ok(layout)
+        };
+        let g1 = layout(G1::NAME, 1)?;
+        let g2 = layout(G2::NAME, 2)?;
+        Ok(Self {
+            g1: reader::read_point::<G1>(g1, 0)?,
+            g2: reader::read_point::<G2>(g2, 0)?,
+            tau_g2: reader::read_point::<G2>(g2, 1)?,
+        })
+    }
+
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = MAGIC.to_vec();
+        self.g1.encode(Encoding::Compressed, &mut bytes);
+        self.g2.encode(Encoding::Compressed, &mut bytes);
+        self.tau_g2.encode(Encoding::Compressed, &mut bytes);
+        bytes
+    }
+
+    /// Parses a verifier key written by `to_bytes`, checking that its generators are the standard
+    /// ones.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self> {
+        let g1_size = G1::encoded_size(Encoding::Compressed);
+        let g2_size = G2::encoded_size(Encoding::Compressed);
+        if bytes.len() != MAGIC.len() + g1_size + 2 * g2_size {
+            return Err(anyhow!(
+                "a verifier key is {} bytes long, got {}",
+                MAGIC.len() + g1_size + 2 * g2_size,
+                bytes.len()
+            ));
+        }
+        let (magic, bytes) = bytes.split_at(MAGIC.len());
+        if magic != MAGIC {
+            return Err(anyhow!("not a verifier key"));
+        }
+        let (g1, bytes) = bytes.split_at(g1_size);
+        let (g2, tau_g2) = bytes.split_at(g2_size);
+        let key = Self {
+            g1: G1::decode(Encoding::Compressed, g1).context("parsing the G1 generator")?,
+            g2: G2::decode(Encoding::Compressed, g2).context("parsing the G2 generator")?,
+            tau_g2: G2::decode(Encoding::Compressed, tau_g2).context("parsing tau·G2")?,
+        };
+        if key.g1 != G1::generator() || key.g2 != G2::generator() {
+            return Err(anyhow!(
+                "the verifier key isn't based on the standard generators"
+            ));
+        }
+        Ok(key)
+    }
+
+    pub fn load(path: &str) -> Result<Self> {
+        let bytes = std::fs::read(path).with_context(|| format!("reading {}", path))?;
+        Self::from_bytes(&bytes).with_context(|| format!("parsing {}", path))
+    }
+
+    pub fn save(&self, path: &str) -> Result<()> {
+        std::fs::write(path, self.to_bytes()).with_context(|| format!("writing {}", path))
+    }
+}
@@ -0,0 +1,154 @@
+//! Truncated views of a set.
+//!
+//! A view declares a power-of-two prefix of a larger set, e.g. the first 2^10 points of a 2^20
+//! set, so that SRSs for several degrees can share the same chunk files. A view file only lists the
+//! layout and the chunk entries the prefix spans; reads through it are bounded by its count. A
+//! chunk straddling the end of the prefix is listed whole, as it's shared with the full set.
+
+use crate::curve::Point;
+use crate::layout::Layout;
+use crate::manifest::{ChunkEntry, Manifest};
+use crate::reader;
+use anyhow::{Context, Result, anyhow};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+pub const VERSION: u32 = 1;
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CurveView {
+    /// Number of points in the view.
+    pub count: u64,
+    /// Layout of the full set.
+    pub layout: Layout,
+    /// Entries of the chunks spanned by the view, sorted by index.
+    pub chunks: Vec<ChunkEntry>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct View {
+    pub version: u32,
+    /// Path of the manifest of the full set.
+    pub manifest: String,
+    /// Base-2 logarithm of the number of points in the view.
+    pub log_count: u32,
+    /// Curve views keyed by curve name ("G1", "G2").
+    pub curves: BTreeMap<String, CurveView>,
+}
+
+/// Returns the path of the view of 2^`log_count` points of the set whose manifest is at
+/// `manifest_path`, e.g. `params.view-10.json` for `params.json`.
+pub fn path(manifest_path: &str, log_count: u32) -> String {
+    let stem = manifest_path.strip_suffix(".json").unwrap_or(manifest_path);
+    format!("{}.view-{}.json", stem, log_count)
+}
+
+impl View {
+    /// Creates the view of the first 2^`log_count` points of each curve of `manifest`, or of all
+    /// the points of curves with fewer. The curves must be plain, as the prefix of a reverse or
+    /// strided set doesn't hold the powers up to a degree.
+    pub fn new(manifest: &Manifest, manifest_path: &str, log_count: u32) -> Result<Self> {
+        let limit = 1u64
+            .checked_shl(log_count)
+            .ok_or_else(|| anyhow!("invalid view size 2^{}", log_count))?;
+        if let Some(name) = manifest
+            .curves
+            .iter()
+            .find_map(|(name, curve)| (!curve.layout.is_plain()).then_some(name))
+        {
+            return Err(anyhow!(
+                "the {} set doesn't hold consecutive powers of tau times the standard generator, \
+                 its prefixes aren't degree views",
+                name
+            ));
+        }
+        let curves = manifest
+            .curves
+            .iter()
+            .map(|(name, curve)| {
+                let count = curve.layout.count.min(limit);
+                let chunks = count.div_ceil(curve.layout.chunk_length) as usize;
+                let view = CurveView {
+                    count,
+                    layout: curve.layout.clone(),
+                    chunks: curve.chunks[..chunks.min(curve.chunks.len())].to_vec(),
+                };
+                (name.clone(), view)
+            })
+            .collect();
+        Ok(Self {
+            version: VERSION,
+            manifest: manifest_path.to_string(),
+            log_count,
+            curves,
+        })
+    }
+
+    pub fn load(path: &str) -> Result<Self> {
+        let json = std::fs::read_to_string(path).with_context(|| format!("reading {}", path))?;
+        let view: Self =
+            serde_json::from_str(json.as_str()).with_context(|| format!("parsing {}", path))?;
+        if view.version != VERSION {
+            return Err(anyhow!(
+                "{}: unsupported view version {}",
+                path,
+                view.version
+            ));
+        }
+        Ok(view)
+    }
+
+    pub fn save(&self, path: &str) -> Result<()> {
+        let mut json = serde_json::to_string_pretty(self)?;
+        json.push('\n');
+        std::fs::write(path, json).with_context(|| format!("writing {}", path))
+    }
+
+    /// Reads the point at global `index` of the `P` set, failing if it's beyond the view.
+    pub fn read_point<P: Point>(&self, index: u64) -> Result<P> {
+        let curve = self
+            .curves
+            .get(P::NAME)
+            .ok_or_else(|| anyhow!("the view has no {} entry", P::NAME))?;
+        if index >= curve.count {
+            return Err(anyhow!(
+                "index {} is beyond the view, which has {} {} points",
+                index,
+                curve.count,
+                P::NAME
+            ));
+        }
+        reader::read_point::<P>(&curve.layout, index)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::curve::G1;
+    use blstrs::Scalar;
+    use group::Group;
+
+    #[test]
+    fn views_bound_the_readable_points() {
+        let dir = tempfile::tempdir().unwrap();
+        let tau = Scalar::from(7u64);
+        let mut manifest = Manifest::default();
+        let curve = Layout::dense(dir.path(), 10, 4).write_set::<G1>(&tau);
+        manifest.curves.insert(G1::NAME.to_string(), curve);
+
+        let view = View::new(&manifest, "params.json", 3).unwrap();
+        assert_eq!(view.curves[G1::NAME].count, 8);
+        assert_eq!(view.curves[G1::NAME].chunks.len(), 2);
+        assert_eq!(
+            view.read_point::<G1>(7).unwrap(),
+            G1::generator() * crate::powers::power(&tau, 7)
+        );
+        assert!(view.read_point::<G1>(8).is_err());
+
+        let view = View::new(&manifest, "params.json", 4).unwrap();
+        assert_eq!(view.curves[G1::NAME].count, 10);
+        assert!(view.read_point::<G1>(9).is_ok());
+        assert!(view.read_point::<G1>(10).is_err());
+    }
+}
@@ -0,0 +1,42 @@
+mod common;
+
+use common::{load_manifest, run_ok, tau, temp_dir};
+use generate_params::chunk;
+use generate_params::curve::{G1, G2, Point};
+
+#[test]
+fn chunks_are_block_aligned_and_decode_to_their_counts() {
+    let dir = temp_dir();
+    run_ok(
+        dir.path(),
+        &[
+            "--g1-count",
+            "25",
+            "--g2-count",
+            "3",
+            "--tau",
+            &tau(7),
+            "--block-size",
+            "512",
+        ],
+    );
+    let manifest = load_manifest(dir.path(), "params.json");
+    let g1 = &manifest.curves[G1::NAME];
+    let g2 = &manifest.curves[G2::NAME];
+    assert!(g1.chunks.len() > 1);
+    for entry in g1.chunks.iter().chain(&g2.chunks) {
+        assert_eq!(std::fs::metadata(&entry.path).unwrap().len(), 512);
+    }
+    let g1_points: usize = g1
+        .chunks
+        .iter()
+        .map(|entry| chunk::read::<G1>(&entry.path).unwrap().len())
+        .sum();
+    assert_eq!(g1_points, 25);
+    let g2_points: usize = g2
+        .chunks
+        .iter()
+        .map(|entry| chunk::read::<G2>(&entry.path).unwrap().len())
+        .sum();
+    assert_eq!(g2_points, 3);
+}
@@ -0,0 +1,38 @@
+mod common;
+
+use blstrs::Scalar;
+use common::{generate, run_err, run_ok, tau, temp_dir};
+use generate_params::curve::{Encoding, G1, G2, Point};
+use group::Group;
+
+/// Returns the `verify-against-ceremony` arguments of a ceremony whose tau is `value`.
+fn ceremony(value: u64) -> Vec<String> {
+    let tau = Scalar::from(value);
+    let (mut g1, mut g2) = (vec![], vec![]);
+    (G1::generator() * tau).encode(Encoding::Compressed, &mut g1);
+    (G2::generator() * tau).encode(Encoding::Compressed, &mut g2);
+    vec![
+        "verify-against-ceremony".to_string(),
+        "--tau-g1".to_string(),
+        hex::encode(g1),
+        "--tau-g2".to_string(),
+        hex::encode(g2),
+    ]
+}
+
+#[test]
+fn sets_are_checked_against_the_ceremony_output() {
+    let dir = temp_dir();
+    generate(dir.path(), &["--tau", &tau(7)]);
+    let args = ceremony(7);
+    run_ok(
+        dir.path(),
+        &args.iter().map(String::as_str).collect::<Vec<_>>(),
+    );
+    let args = ceremony(8);
+    let stderr = run_err(
+        dir.path(),
+        &args.iter().map(String::as_str).collect::<Vec<_>>(),
+    );
+    assert!(stderr.contains("the ceremony's tau·G"), "{}", stderr);
+}
@@ -0,0 +1,19 @@
+mod common;
+
+use common::{generate, run_err, run_ok, tau, temp_dir};
+
+#[test]
+fn degrees_are_checked_against_the_powers_of_the_set() {
+    // 10 G1 powers, for polynomials of degree 9 at most.
+    let dir = temp_dir();
+    generate(dir.path(), &["--tau", &tau(7)]);
+    let output = run_ok(dir.path(), &["check-degree", "--need", "9"]);
+    assert!(String::from_utf8_lossy(&output.stdout).starts_with("Yes"));
+    let error = run_err(dir.path(), &["check-degree", "--need", "12"]);
+    assert!(
+        error.contains("the G1 set has 10 powers, 3 short of the 13 needed"),
+        "{}",
+        error
+    );
+    assert!(error.contains("no, params.json can't commit"), "{}", error);
+}
@@ -0,0 +1,116 @@
+//! Helpers shared by the integration tests, which run the binary on small sets in temporary
+//! directories. With `--features toy`, the binary generates toy sets, which makes them fast.
+
+#![allow(dead_code)]
+
+use generate_params::manifest::Manifest;
+use std::path::Path;
+use std::process::{Child, Command, Output, Stdio};
+use tempfile::TempDir;
+
+/// Returns a fresh temporary directory, deleted when dropped.
+pub fn temp_dir() -> TempDir {
+    tempfile::tempdir().unwrap()
+}
+
+/// Returns `value` as a `--tau` argument, i.e. 32 hex-encoded big-endian bytes.
+pub fn tau(value: u64) -> String {
+    format!("{:064x}", value)
+}
+
+/// Runs the binary in `dir` with `args`.
+pub fn run(dir: &Path, args: &[&str]) -> Output {
+    run_with_env(dir, &[], args)
+}
+
+/// Starts the binary in `dir` with `args`, discarding its output.
+pub fn spawn(dir: &Path, args: &[&str]) -> Child {
+    Command::new(env!("CARGO_BIN_EXE_generate_params"))
+        .current_dir(dir)
+        .args(args)
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn()
+        .unwrap()
+}
+
+/// Like `run`, with the environment variables `env` set.
+pub fn run_with_env(dir: &Path, env: &[(&str, &str)], args: &[&str]) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_generate_params"))
+        .current_dir(dir)
+        .envs(env.iter().copied())
+        .args(args)
+        .output()
+        .unwrap()
+}
+
+/// Like `run`, but fails the test with the output of the binary if it fails.
+pub fn run_ok(dir: &Path, args: &[&str]) -> Output {
+    let output = run(dir, args);
+    assert!(
+        output.status.success(),
+        "{:?} failed:\n{}{}",
+        args,
+        String::from_utf8_lossy(&output.stdout),
+        String::from_utf8_lossy(&output.stderr)
+    );
+    output
+}
+
+/// Like `run`, but fails the test if the binary succeeds, and returns its stderr.
+pub fn run_err(dir: &Path, args: &[&str]) -> String {
+    let output = run(dir, args);
+    assert!(!output.status.success(), "{:?} succeeded", args);
+    String::from_utf8_lossy(&output.stderr).into_owned()
+}
+
+/// Arguments of a small set: 10 G1 and 3 G2 points, in chunks of 4.
+pub const SMALL: [&str; 8] = [
+    "--g1-count",
+    "10",
+    "--g2-count",
+    "3",
+    "--g1-chunk-length",
+    "4",
+    "--g2-chunk-length",
+    "4",
+];
+
+/// Generates the `SMALL` set into `dir` with `args`, which must provide tau.
+pub fn generate(dir: &Path, args: &[&str]) -> Output {
+    run_ok(dir, &[&SMALL[..], args].concat())
+}
+
+/// Like `generate`, but fails the test if generation succeeds, and returns its stderr.
+pub fn generate_err(dir: &Path, args: &[&str]) -> String {
+    run_err(dir, &[&SMALL[..], args].concat())
+}
+
+/// Returns the names and contents of the files of `dir`, sorted by name.
+pub fn files(dir: &Path) -> Vec<(String, Vec<u8>)> {
+    let mut files: Vec<_> = std::fs::read_dir(dir)
+        .unwrap()
+        .map(|entry| entry.unwrap())
+        .filter(|entry| entry.file_type().unwrap().is_file())
+        .map(|entry| {
+            let name = entry.file_name().into_string().unwrap();
+            (name, std::fs::read(entry.path()).unwrap())
+        })
+        .collect();
+    files.sort();
+    files
+}
+
+/// Loads the manifest at `name` in `dir`, with the paths of its chunks and its file patterns made
+/// absolute, so that the test can read the set from its own working directory.
+pub fn load_manifest(dir: &Path, name: &str) -> Manifest {
+    let mut manifest = Manifest::load(dir.join(name).to_str().unwrap()).unwrap();
+    let absolute = |path: &str| dir.join(path).to_str().unwrap().to_string();
+    for curve in manifest.curves.values_mut() {
+        curve.layout.pattern = absolute(curve.layout.pattern.as_str());
+        for chunk in &mut curve.chunks {
+            chunk.path = absolute(chunk.path.as_str());
+        }
+    }
+    manifest
+}
@@ -0,0 +1,28 @@
+mod common;
+
+use common::{generate, run_err, tau, temp_dir};
+
+#[test]
+fn an_impossible_deadline_is_refused_before_starting() {
+    let dir = temp_dir();
+    let error = run_err(
+        dir.path(),
+        &[
+            "--g1-count",
+            "1000000000",
+            "--tau",
+            &tau(7),
+            "--deadline",
+            "60",
+        ],
+    );
+    assert!(
+        error.contains("more than the deadline of 60 seconds"),
+        "{}",
+        error
+    );
+    assert!(std::fs::read_dir(dir.path()).unwrap().next().is_none());
+
+    generate(dir.path(), &["--tau", &tau(7), "--deadline", "3600"]);
+    assert!(dir.path().join("params.json").exists());
+}
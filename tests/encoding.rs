@@ -0,0 +1,44 @@
+mod common;
+
+use blstrs::Scalar;
+use common::{generate, load_manifest, tau, temp_dir};
+use generate_params::chunk;
+use generate_params::curve::{Encoding, G1, G2, Point};
+use generate_params::reader;
+
+/// Checks that the `P` set described by the manifest in `dir` holds the powers of 7 and was
+/// written with `encoding`.
+fn check<P: Point>(dir: &std::path::Path, encoding: Encoding) {
+    let manifest = load_manifest(dir, "params.json");
+    let curve = &manifest.curves[P::NAME];
+    assert_eq!(curve.layout.encoding, encoding);
+    for entry in &curve.chunks {
+        let header = chunk::read_file_header(entry.path.as_str()).unwrap();
+        assert_eq!(header.encoding, encoding);
+    }
+    for index in 0..curve.layout.count {
+        let expected = P::generator() * Scalar::from(7u64.pow(index as u32));
+        assert_eq!(
+            reader::read_point::<P>(&curve.layout, index).unwrap(),
+            expected
+        );
+    }
+}
+
+#[test]
+fn curves_keep_their_own_encodings() {
+    let dir = temp_dir();
+    generate(
+        dir.path(),
+        &[
+            "--tau",
+            &tau(7),
+            "--g1-encoding",
+            "compressed",
+            "--g2-encoding",
+            "uncompressed",
+        ],
+    );
+    check::<G1>(dir.path(), Encoding::Compressed);
+    check::<G2>(dir.path(), Encoding::Uncompressed);
+}
@@ -0,0 +1,38 @@
+mod common;
+
+use blstrs::Scalar;
+use common::{generate, load_manifest, tau, temp_dir};
+use ff::Field;
+use generate_params::curve::{G1, G2, Point};
+use generate_params::reader;
+use group::Group;
+
+/// Checks that point j of each curve of the set in `dir` is tau^(2j + first)·G.
+fn check(dir: &std::path::Path, first: u64) {
+    let manifest = load_manifest(dir, "params.json");
+    let power = |j: u64| Scalar::from(7u64).pow_vartime([2 * j + first]);
+    let g1 = &manifest.curves[G1::NAME].layout;
+    for j in 0..10 {
+        let point = reader::read_point::<G1>(g1, j).unwrap();
+        assert_eq!(point, G1::generator() * power(j), "G1 point {}", j);
+    }
+    let g2 = &manifest.curves[G2::NAME].layout;
+    for j in 0..3 {
+        let point = reader::read_point::<G2>(g2, j).unwrap();
+        assert_eq!(point, G2::generator() * power(j), "G2 point {}", j);
+    }
+}
+
+#[test]
+fn even_powers_are_the_powers_of_tau_squared() {
+    let dir = temp_dir();
+    generate(dir.path(), &["--tau", &tau(7), "--even-powers"]);
+    check(dir.path(), 0);
+}
+
+#[test]
+fn odd_powers_are_tau_times_the_even_powers() {
+    let dir = temp_dir();
+    generate(dir.path(), &["--tau", &tau(7), "--odd-powers"]);
+    check(dir.path(), 1);
+}
@@ -0,0 +1,65 @@
+mod common;
+
+use blstrs::Scalar;
+use common::{generate, run_ok, tau, temp_dir};
+use generate_params::curve::{Encoding, G1, G2, Point};
+use generate_params::manifest::Manifest;
+use group::Group;
+
+fn explain(dir: &std::path::Path) -> String {
+    String::from_utf8(run_ok(dir, &["explain-security"]).stdout).unwrap()
+}
+
+#[test]
+fn sets_are_assessed_as_single_contributor() {
+    let dir = temp_dir();
+    generate(dir.path(), &["--tau", &tau(7)]);
+    let report = explain(dir.path());
+    let manifest = Manifest::load(dir.path().join("params.json").to_str().unwrap()).unwrap();
+    assert!(report.contains("Set: params.json (10 G1 points, 3 G2 points)"));
+    assert!(report.contains(&format!("Fingerprint: {}", manifest.fingerprint())));
+    assert!(report.contains("Contributions: 1"));
+    assert!(report.contains("can forge proofs"));
+}
+
+#[test]
+fn sets_checked_against_a_ceremony_are_assessed_as_multi_contributor() {
+    let dir = temp_dir();
+    generate(dir.path(), &["--tau", &tau(7)]);
+    let (mut tau_g1, mut tau_g2) = (vec![], vec![]);
+    (G1::generator() * Scalar::from(7u64)).encode(Encoding::Compressed, &mut tau_g1);
+    (G2::generator() * Scalar::from(7u64)).encode(Encoding::Compressed, &mut tau_g2);
+    run_ok(
+        dir.path(),
+        &[
+            "verify-against-ceremony",
+            "--tau-g1",
+            &hex::encode(&tau_g1),
+            "--tau-g2",
+            &hex::encode(&tau_g2),
+            "--record",
+            "--contributions",
+            "141416",
+        ],
+    );
+    let report = explain(dir.path());
+    assert!(
+        report.contains(&format!(
+            "Contributions: 141416 (multi-party ceremony with tau·G1 = {})",
+            hex::encode(&tau_g1)
+        )),
+        "{}",
+        report
+    );
+    assert!(!report.contains("can forge proofs"));
+
+    // The record doesn't carry over to a modified set.
+    let path = dir.path().join("params.json");
+    let path = path.to_str().unwrap();
+    let mut manifest = Manifest::load(path).unwrap();
+    manifest.curves.get_mut(G1::NAME).unwrap().chunks[0].blake2b = "00".repeat(32);
+    manifest.save(path).unwrap();
+    let report = explain(dir.path());
+    assert!(report.contains("Ceremony record: ignored"), "{}", report);
+    assert!(report.contains("Contributions: 1"));
+}
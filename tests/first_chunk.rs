@@ -0,0 +1,27 @@
+mod common;
+
+use common::{generate_err, temp_dir};
+
+#[test]
+fn a_bad_first_chunk_aborts_the_run() {
+    let dir = temp_dir();
+    // A single attempt, whose first G1 chunk is corrupted.
+    let error = generate_err(
+        dir.path(),
+        &[
+            "--validate-first-chunk",
+            "--verify-and-retry",
+            "0",
+            "--inject-fault-attempts",
+            "1",
+        ],
+    );
+    assert!(
+        error.contains("g1_0.bin doesn't hold consecutive powers of tau once read back"),
+        "{}",
+        error
+    );
+    assert!(!dir.path().join("g1_1.bin").exists());
+    assert!(!dir.path().join("g1_2.bin").exists());
+    assert!(!dir.path().join("params.json").exists());
+}
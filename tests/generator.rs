@@ -0,0 +1,77 @@
+mod common;
+
+use blstrs::Scalar;
+use common::{files, generate, load_manifest, tau, temp_dir};
+use generate_params::curve::{Encoding, G1, G2, Point};
+use generate_params::reader;
+use group::Group;
+
+fn encode<P: Point>(point: P) -> String {
+    let mut bytes = vec![];
+    point.encode(Encoding::Compressed, &mut bytes);
+    hex::encode(bytes)
+}
+
+#[test]
+fn the_standard_generator_gives_the_default_set() {
+    let default = temp_dir();
+    generate(default.path(), &["--tau", &tau(7)]);
+    let pinned = temp_dir();
+    generate(
+        pinned.path(),
+        &[
+            "--tau",
+            &tau(7),
+            "--g1-generator",
+            &encode(G1::generator()),
+            "--g2-generator",
+            &encode(G2::generator()),
+        ],
+    );
+    assert_eq!(files(pinned.path()), files(default.path()));
+}
+
+#[test]
+fn another_generator_multiplies_every_power() {
+    let default = temp_dir();
+    generate(default.path(), &["--tau", &tau(7)]);
+    let custom = temp_dir();
+    let three = Scalar::from(3u64);
+    generate(
+        custom.path(),
+        &[
+            "--tau",
+            &tau(7),
+            "--g1-generator",
+            &encode(G1::generator() * three),
+            "--g2-generator",
+            &encode(G2::generator() * three),
+        ],
+    );
+    let default = load_manifest(default.path(), "params.json");
+    let custom = load_manifest(custom.path(), "params.json");
+    let (g1, custom_g1) = (
+        &default.curves[G1::NAME].layout,
+        &custom.curves[G1::NAME].layout,
+    );
+    for j in 0..10 {
+        assert_eq!(
+            reader::read_point::<G1>(custom_g1, j).unwrap(),
+            reader::read_point::<G1>(g1, j).unwrap() * three,
+            "G1 point {}",
+            j
+        );
+    }
+    let (g2, custom_g2) = (
+        &default.curves[G2::NAME].layout,
+        &custom.curves[G2::NAME].layout,
+    );
+    for j in 0..3 {
+        assert_eq!(
+            reader::read_point::<G2>(custom_g2, j).unwrap(),
+            reader::read_point::<G2>(g2, j).unwrap() * three,
+            "G2 point {}",
+            j
+        );
+    }
+}
@@ -0,0 +1,11 @@
+mod common;
+
+use common::{run_ok, temp_dir};
+
+#[test]
+fn help_starts_with_the_usage() {
+    let dir = temp_dir();
+    let output = run_ok(dir.path(), &["--help"]);
+    let help = String::from_utf8(output.stdout).unwrap();
+    assert!(help.starts_with("Usage: generate_params"), "{}", help);
+}
@@ -0,0 +1,24 @@
+mod common;
+
+use common::{generate, run_ok, tau, temp_dir};
+
+const SKIPPED: &str = "records that the points of this set are valid";
+
+#[test]
+fn markers_skip_point_checks_of_the_same_set_only() {
+    let dir = temp_dir();
+    generate(dir.path(), &["--tau", &tau(7)]);
+    let marker = dir.path().join("marker.json");
+    let marker = marker.to_str().unwrap();
+    let validate = ["validate", "--check-points", "--marker", marker];
+    let first = run_ok(dir.path(), &validate);
+    assert!(!String::from_utf8_lossy(&first.stdout).contains(SKIPPED));
+    let second = run_ok(dir.path(), &validate);
+    assert!(String::from_utf8_lossy(&second.stdout).contains(SKIPPED));
+
+    // The marker is for a set with another fingerprint.
+    let other = temp_dir();
+    generate(other.path(), &["--tau", &tau(8)]);
+    let output = run_ok(other.path(), &validate);
+    assert!(!String::from_utf8_lossy(&output.stdout).contains(SKIPPED));
+}
@@ -0,0 +1,37 @@
+mod common;
+
+use common::{generate, generate_err, tau, temp_dir};
+
+#[test]
+fn progress_and_log_streams_do_not_mix() {
+    let dir = temp_dir();
+    let output = generate(
+        dir.path(),
+        &["--tau", &tau(7), "--progress-fd", "2", "--log-fd", "1"],
+    );
+    let log = String::from_utf8(output.stdout).unwrap();
+    assert!(log.lines().count() > 0);
+    for line in log.lines() {
+        let record: serde_json::Value = serde_json::from_str(line).unwrap();
+        assert!(record["message"].is_string(), "{}", line);
+    }
+    let progress = String::from_utf8(output.stderr).unwrap();
+    assert!(progress.contains("pts generated"));
+    assert!(!progress.contains("time_ms"));
+    assert!(!progress.contains("written"));
+}
+
+#[test]
+fn unopened_log_descriptors_are_rejected() {
+    let dir = temp_dir();
+    // Nothing is redirected to descriptor 63 of the child.
+    let error = generate_err(dir.path(), &["--tau", &tau(7), "--log-fd", "63"]);
+    assert!(
+        error.contains("file descriptor 63 is not open"),
+        "{}",
+        error
+    );
+    let error = generate_err(dir.path(), &["--tau", &tau(7), "--log-fd", "0"]);
+    assert!(error.contains("stdin"), "{}", error);
+    assert!(std::fs::read_dir(dir.path()).unwrap().next().is_none());
+}
@@ -0,0 +1,36 @@
+mod common;
+
+use common::{run_err, tau, temp_dir};
+use std::time::{Duration, Instant};
+
+#[test]
+fn a_generator_panic_stops_the_run_promptly() {
+    let dir = temp_dir();
+    let start = Instant::now();
+    // Without cancellation, the G2 set alone would take 10 seconds.
+    let error = run_err(
+        dir.path(),
+        &[
+            "--g1-count",
+            "10",
+            "--g2-count",
+            "200",
+            "--g1-chunk-length",
+            "4",
+            "--g2-chunk-length",
+            "4",
+            "--max-points-per-second",
+            "20",
+            "--tau",
+            &tau(7),
+            "--panic-in-g1",
+        ],
+    );
+    assert!(start.elapsed() < Duration::from_secs(5));
+    assert!(
+        error.contains("the G1 generator thread panicked"),
+        "{}",
+        error
+    );
+    assert!(!dir.path().join("params.json").exists());
+}
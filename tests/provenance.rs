@@ -0,0 +1,32 @@
+mod common;
+
+use common::{files, generate, load_manifest, tau, temp_dir};
+
+#[test]
+fn provenance_is_recorded_without_changing_the_fingerprint() {
+    let plain = temp_dir();
+    generate(plain.path(), &["--tau", &tau(7)]);
+    let recorded = temp_dir();
+    generate(recorded.path(), &["--tau", &tau(7), "--record-provenance"]);
+    let plain_manifest = load_manifest(plain.path(), "params.json");
+    let manifest = load_manifest(recorded.path(), "params.json");
+    assert_eq!(manifest.fingerprint(), plain_manifest.fingerprint());
+    for curve in manifest.curves.values() {
+        for chunk in &curve.chunks {
+            let provenance = chunk.provenance.as_ref().unwrap();
+            assert!(provenance.time_ms > 0);
+            assert!(provenance.pid > 0);
+        }
+    }
+    for curve in plain_manifest.curves.values() {
+        assert!(curve.chunks.iter().all(|chunk| chunk.provenance.is_none()));
+    }
+    // Only the manifests differ.
+    let chunks = |dir: &std::path::Path| {
+        files(dir)
+            .into_iter()
+            .filter(|(name, _)| name.ends_with(".bin"))
+            .collect::<Vec<_>>()
+    };
+    assert_eq!(chunks(recorded.path()), chunks(plain.path()));
+}
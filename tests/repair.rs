@@ -0,0 +1,90 @@
+mod common;
+
+use common::{files, generate, run_err, run_ok, tau, temp_dir};
+use generate_params::manifest::Manifest;
+use std::path::Path;
+
+/// Flips a bit of the last point of the chunk at `path`.
+fn corrupt(path: &Path) {
+    let mut data = std::fs::read(path).unwrap();
+    let last = data.len() - 1;
+    data[last] ^= 1;
+    std::fs::write(path, data).unwrap();
+}
+
+#[test]
+fn repairing_a_corrupted_chunk_restores_the_set() {
+    let dir = temp_dir();
+    generate(dir.path(), &["--tau", &tau(7)]);
+    let manifest = dir.path().join("params.json");
+    let fingerprint = Manifest::load(manifest.to_str().unwrap())
+        .unwrap()
+        .fingerprint();
+    corrupt(&dir.path().join("g1_1.bin"));
+    run_err(dir.path(), &["validate", "--check-hashes"]);
+
+    let wrong = run_err(
+        dir.path(),
+        &[
+            "repair-chunk",
+            "--curve",
+            "g1",
+            "--index",
+            "1",
+            "--tau",
+            &tau(8),
+        ],
+    );
+    assert!(wrong.contains("doesn't match"));
+    run_ok(
+        dir.path(),
+        &[
+            "repair-chunk",
+            "--curve",
+            "g1",
+            "--index",
+            "1",
+            "--tau",
+            &tau(7),
+        ],
+    );
+    run_ok(dir.path(), &["validate", "--check-hashes"]);
+    let repaired = Manifest::load(manifest.to_str().unwrap()).unwrap();
+    assert_eq!(repaired.fingerprint(), fingerprint);
+}
+
+#[test]
+fn repairing_from_the_failure_list_restores_the_set() {
+    let (reference, dir) = (temp_dir(), temp_dir());
+    generate(reference.path(), &["--tau", &tau(7)]);
+    generate(dir.path(), &["--tau", &tau(7)]);
+    for chunk in ["g1_0.bin", "g1_2.bin", "g2_0.bin"] {
+        corrupt(&dir.path().join(chunk));
+    }
+    run_err(
+        dir.path(),
+        &["validate", "--check-hashes", "--failures", "failures.json"],
+    );
+    let output = run_ok(
+        dir.path(),
+        &[
+            "repair",
+            "--repair-from-verify",
+            "failures.json",
+            "--tau",
+            &tau(7),
+        ],
+    );
+    let log = String::from_utf8(output.stdout).unwrap();
+    assert!(log.contains("g1_0.bin") && log.contains("g1_2.bin") && log.contains("g2_0.bin"));
+    assert!(!log.contains("g1_1.bin"));
+    // The G2 set has a single chunk, so tau can only be checked for G1.
+    assert_eq!(
+        log.matches("no chunk left to check the provided tau")
+            .count(),
+        1
+    );
+    std::fs::remove_file(dir.path().join("failures.json")).unwrap();
+    run_ok(dir.path(), &["validate", "--check-hashes"]);
+    assert_eq!(files(dir.path()), files(reference.path()));
+}
@@ -0,0 +1,64 @@
+// Resuming is tested through the `--crash-after-chunk` hook, which only debug builds have.
+#![cfg(debug_assertions)]
+
+mod common;
+
+use common::{files, generate, generate_err, run_ok, tau, temp_dir};
+
+#[test]
+fn resuming_after_any_chunk_gives_the_same_set() {
+    let reference = temp_dir();
+    generate(reference.path(), &["--tau", &tau(7)]);
+    // The G1 set has 3 chunks.
+    for chunk in 0..3 {
+        let dir = temp_dir();
+        let chunk = chunk.to_string();
+        generate_err(
+            dir.path(),
+            &["--tau", &tau(7), "--crash-after-chunk", chunk.as_str()],
+        );
+        assert!(!dir.path().join("params.json").exists());
+        generate(dir.path(), &["--tau", &tau(7), "--resume"]);
+        assert_eq!(
+            files(dir.path()),
+            files(reference.path()),
+            "chunk {}",
+            chunk
+        );
+        run_ok(dir.path(), &["validate", "--check-hashes"]);
+    }
+}
+
+#[test]
+fn resuming_with_another_tau_is_refused() {
+    let dir = temp_dir();
+    generate_err(dir.path(), &["--tau", &tau(7), "--crash-after-chunk", "1"]);
+    let error = generate_err(dir.path(), &["--tau", &tau(8), "--resume"]);
+    assert!(
+        error.contains("wasn't generated with the provided tau"),
+        "{}",
+        error
+    );
+}
+
+#[test]
+fn resuming_keeps_the_format_version_of_the_existing_chunks() {
+    let (reference, dir) = (temp_dir(), temp_dir());
+    generate(
+        reference.path(),
+        &["--tau", &tau(7), "--format-version", "1"],
+    );
+    generate_err(
+        dir.path(),
+        &[
+            "--tau",
+            &tau(7),
+            "--format-version",
+            "1",
+            "--crash-after-chunk",
+            "0",
+        ],
+    );
+    generate(dir.path(), &["--tau", &tau(7), "--resume"]);
+    assert_eq!(files(dir.path()), files(reference.path()));
+}
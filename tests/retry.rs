@@ -0,0 +1,38 @@
+mod common;
+
+use common::{generate, generate_err, run_ok, temp_dir};
+
+#[test]
+fn a_failing_first_attempt_is_retried() {
+    let dir = temp_dir();
+    let output = generate(
+        dir.path(),
+        &["--verify-and-retry", "1", "--inject-fault-attempts", "1"],
+    );
+    let log = String::from_utf8(output.stdout).unwrap();
+    assert!(
+        log.contains("retrying with a fresh tau (attempt 2 of 2)"),
+        "{}",
+        log
+    );
+    assert!(log.contains("Verification passed"));
+    run_ok(
+        dir.path(),
+        &["validate", "--check-hashes", "--check-points"],
+    );
+}
+
+#[test]
+fn retries_are_bounded() {
+    let dir = temp_dir();
+    let error = generate_err(
+        dir.path(),
+        &["--verify-and-retry", "1", "--inject-fault-attempts", "2"],
+    );
+    assert!(
+        error.contains("verification failed after 2 attempts"),
+        "{}",
+        error
+    );
+    assert!(!dir.path().join("params.json").exists());
+}
@@ -0,0 +1,65 @@
+mod common;
+
+use common::{generate, tau, temp_dir};
+use generate_params::chunk;
+use generate_params::curve::{G1, G2, Point};
+use std::path::Path;
+
+/// Returns the points of the `P` set in `dir`, in file order.
+fn points<P: Point>(dir: &Path, chunks: u64) -> Vec<P> {
+    (0..chunks)
+        .flat_map(|index| {
+            let path = dir.join(format!("{}_{}.bin", P::NAME.to_lowercase(), index));
+            chunk::read::<P>(path.to_str().unwrap()).unwrap()
+        })
+        .collect()
+}
+
+#[test]
+fn reverse_sets_are_forward_sets_in_reverse_order() {
+    let (forward, reverse) = (temp_dir(), temp_dir());
+    generate(forward.path(), &["--tau", &tau(7)]);
+    generate(reverse.path(), &["--tau", &tau(7), "--reverse"]);
+    let mut g1 = points::<G1>(reverse.path(), 3);
+    g1.reverse();
+    assert_eq!(g1, points::<G1>(forward.path(), 3));
+    let mut g2 = points::<G2>(reverse.path(), 1);
+    g2.reverse();
+    assert_eq!(g2, points::<G2>(forward.path(), 1));
+}
+
+// Uses the `--crash-after-chunk` hook, which only debug builds have.
+#[cfg(debug_assertions)]
+#[test]
+fn another_tau_is_detected_in_the_last_chunk_of_reverse_sets() {
+    use common::{generate_err, run_err};
+
+    // The last chunk of a reverse set ends with tau^0·G, which is the same for every tau.
+    let dir = temp_dir();
+    generate_err(
+        dir.path(),
+        &["--tau", &tau(7), "--reverse", "--crash-after-chunk", "2"],
+    );
+    let error = generate_err(dir.path(), &["--tau", &tau(8), "--reverse", "--resume"]);
+    assert!(
+        error.contains("wasn't generated with the provided tau"),
+        "{}",
+        error
+    );
+
+    let dir = temp_dir();
+    generate(dir.path(), &["--tau", &tau(7), "--reverse"]);
+    let error = run_err(
+        dir.path(),
+        &[
+            "repair-chunk",
+            "--curve",
+            "g1",
+            "--index",
+            "2",
+            "--tau",
+            &tau(8),
+        ],
+    );
+    assert!(error.contains("doesn't match"), "{}", error);
+}
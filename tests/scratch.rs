@@ -0,0 +1,16 @@
+mod common;
+
+use common::{files, generate, run_ok, tau, temp_dir};
+
+#[test]
+fn scratch_then_finalize_matches_direct_generation() {
+    let (direct, scratch) = (temp_dir(), temp_dir());
+    generate(direct.path(), &["--tau", &tau(7)]);
+    generate(scratch.path(), &["--tau", &tau(7), "--scratch"]);
+    run_ok(
+        scratch.path(),
+        &["finalize", "--scratch-manifest", "params.scratch.json"],
+    );
+    std::fs::remove_file(scratch.path().join("params.scratch.json")).unwrap();
+    assert_eq!(files(direct.path()), files(scratch.path()));
+}
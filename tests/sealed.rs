@@ -0,0 +1,65 @@
+mod common;
+
+use common::{files, run_ok, run_with_env, tau, temp_dir};
+
+const PASSPHRASE: (&str, &str) = ("GENPARAMS_TEST_PASSPHRASE", "correct horse battery staple");
+
+#[test]
+fn sealed_taus_give_g2_sets_consistent_with_the_g1_set() {
+    let reference = temp_dir();
+    run_ok(
+        reference.path(),
+        &["--tau", &tau(7), "--g1-count", "5", "--g2-count", "3"],
+    );
+    let reference = files(reference.path());
+    let chunks = |files: Vec<(String, Vec<u8>)>, prefix: &str| {
+        files
+            .into_iter()
+            .filter(|(name, _)| name.starts_with(prefix))
+            .collect::<Vec<_>>()
+    };
+
+    let g1 = temp_dir();
+    let output = run_with_env(
+        g1.path(),
+        &[PASSPHRASE],
+        &[
+            "--tau",
+            &tau(7),
+            "--g1-count",
+            "5",
+            "--g2-count",
+            "0",
+            "--tau-encrypt",
+            "tau.json",
+            "--passphrase-env",
+            PASSPHRASE.0,
+        ],
+    );
+    assert!(output.status.success(), "{:?}", output);
+    let sealed = g1.path().join("tau.json");
+    let sealed = sealed.to_str().unwrap();
+
+    let g2 = temp_dir();
+    let g2_args = [
+        "--g1-count",
+        "0",
+        "--g2-count",
+        "3",
+        "--tau-sealed",
+        sealed,
+        "--passphrase-env",
+        PASSPHRASE.0,
+    ];
+    let output = run_with_env(g2.path(), &[(PASSPHRASE.0, "wrong")], &g2_args);
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("wrong passphrase"));
+    let output = run_with_env(g2.path(), &[PASSPHRASE], &g2_args);
+    assert!(output.status.success(), "{:?}", output);
+
+    assert_eq!(
+        chunks(files(g1.path()), "g1_"),
+        chunks(reference.clone(), "g1_")
+    );
+    assert_eq!(chunks(files(g2.path()), "g2_"), chunks(reference, "g2_"));
+}
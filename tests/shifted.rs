@@ -0,0 +1,61 @@
+mod common;
+
+use common::{run_err, run_ok, tau, temp_dir};
+use generate_params::curve::{G1, Point};
+use generate_params::manifest::Manifest;
+use generate_params::shifted::{self, SHIFTED, ShiftedBundle};
+use std::path::Path;
+
+/// Writes a bundle of 10 G1 and 2 G2 powers of `value`, shifted by 3, in chunks of 4, to
+/// `bundle` in `dir`.
+fn bundle(dir: &Path, value: u64) {
+    run_ok(
+        dir,
+        &[
+            "bundle",
+            "--tau",
+            &tau(value),
+            "--g1-count",
+            "10",
+            "--shift",
+            "3",
+            "--chunk-length",
+            "4",
+        ],
+    );
+}
+
+#[test]
+fn foreign_chunks_in_bundles_are_detected() {
+    let (dir, foreign) = (temp_dir(), temp_dir());
+    bundle(dir.path(), 7);
+    bundle(foreign.path(), 8);
+    run_ok(dir.path(), &["verify-bundle-consistency"]);
+
+    let chunk = Path::new("bundle").join(SHIFTED).join("g1_1.bin");
+    std::fs::copy(foreign.path().join(&chunk), dir.path().join(&chunk)).unwrap();
+    let error = run_err(dir.path(), &["verify-bundle-consistency"]);
+    assert!(error.contains("doesn't have the hash"), "{}", error);
+
+    // Also with the hashes updated, so that only the points give it away.
+    let manifest_path = |dir: &Path| dir.join("bundle").join(SHIFTED).join("params.json");
+    let load = |dir: &Path| Manifest::load(manifest_path(dir).to_str().unwrap()).unwrap();
+    let mut manifest = load(dir.path());
+    let foreign_manifest = load(foreign.path());
+    manifest.curves.get_mut(G1::NAME).unwrap().chunks[1].blake2b =
+        foreign_manifest.curves[G1::NAME].chunks[1].blake2b.clone();
+    manifest
+        .save(manifest_path(dir.path()).to_str().unwrap())
+        .unwrap();
+    let bundle_path = shifted::path(dir.path().join("bundle").to_str().unwrap());
+    let mut bundle = ShiftedBundle::load(bundle_path.as_str()).unwrap();
+    for set in &mut bundle.sets {
+        if set.label == SHIFTED {
+            set.fingerprint = manifest.fingerprint();
+        }
+    }
+    bundle.save(bundle_path.as_str()).unwrap();
+    let error = run_err(dir.path(), &["verify-bundle-consistency"]);
+    assert!(error.contains("shifted G1"), "{}", error);
+    assert!(!error.contains("hash"), "{}", error);
+}
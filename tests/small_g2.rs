@@ -0,0 +1,18 @@
+mod common;
+
+use common::{files, run_ok, tau, temp_dir};
+
+#[test]
+fn small_g2_sets_are_generated_on_the_main_thread() {
+    let args = ["--tau", &tau(7), "--g1-count", "10", "--g2-count", "2"];
+    let reference = temp_dir();
+    let output = run_ok(reference.path(), &args);
+    assert!(!String::from_utf8_lossy(&output.stdout).contains("on the main thread"));
+    let dir = temp_dir();
+    let output = run_ok(
+        dir.path(),
+        &[&args[..], &["--no-g2-thread-when-small"]].concat(),
+    );
+    assert!(String::from_utf8_lossy(&output.stdout).contains("Generating G2 on the main thread"));
+    assert_eq!(files(dir.path()), files(reference.path()));
+}
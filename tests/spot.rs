@@ -0,0 +1,55 @@
+mod common;
+
+use common::{files, run_ok, spawn, tau, temp_dir};
+use std::process::Command;
+use std::time::{Duration, Instant};
+
+/// Arguments of a set slow enough to be interrupted: 40 G1 points in chunks of 16 at 20 points
+/// per second, and 2 G2 points.
+const SLOW: [&str; 10] = [
+    "--g1-count",
+    "40",
+    "--g2-count",
+    "2",
+    "--g1-chunk-length",
+    "16",
+    "--g2-chunk-length",
+    "16",
+    "--max-points-per-second",
+    "20",
+];
+
+#[test]
+fn sigterm_in_spot_mode_checkpoints_the_current_chunk() {
+    let tau = tau(7);
+    let reference = temp_dir();
+    run_ok(reference.path(), &[&SLOW[..8], &["--tau", &tau]].concat());
+
+    let dir = temp_dir();
+    let args = [&SLOW[..], &["--tau", &tau, "--spot-mode"]].concat();
+    let mut child = spawn(dir.path(), &args);
+    // The G2 set is written first, then the first G1 chunk takes 0.8 s.
+    let start = Instant::now();
+    while !dir.path().join("g2_0.bin").exists() {
+        assert!(start.elapsed() < Duration::from_secs(30), "no G2 chunk");
+        std::thread::sleep(Duration::from_millis(10));
+    }
+    std::thread::sleep(Duration::from_millis(300));
+    let status = Command::new("kill")
+        .args(["-TERM", &child.id().to_string()])
+        .status()
+        .unwrap();
+    assert!(status.success());
+    let start = Instant::now();
+    assert!(!child.wait().unwrap().success());
+    assert!(start.elapsed() < Duration::from_secs(5));
+    // The points of the interrupted chunk are on disk, not lost.
+    assert!(dir.path().join("g1_0.bin.partial").exists());
+    assert!(!dir.path().join("params.json").exists());
+
+    run_ok(
+        dir.path(),
+        &[&SLOW[..8], &["--tau", &tau, "--spot-mode"]].concat(),
+    );
+    assert_eq!(files(dir.path()), files(reference.path()));
+}
@@ -0,0 +1,35 @@
+mod common;
+
+use common::{SMALL, files, generate, generate_err, run_with_env, tau, temp_dir};
+
+#[test]
+fn seed_hex_and_seed_file_derive_the_same_tau() {
+    let (hex, file) = (temp_dir(), temp_dir());
+    generate(hex.path(), &["--seed-hex", "0x0102030405"]);
+    std::fs::write(file.path().join("seed"), [1, 2, 3, 4, 5]).unwrap();
+    generate(file.path(), &["--seed-from-file", "seed"]);
+    std::fs::remove_file(file.path().join("seed")).unwrap();
+    assert_eq!(files(hex.path()), files(file.path()));
+}
+
+#[test]
+fn tau_env_derives_the_same_tau_as_tau() {
+    let (arg, env) = (temp_dir(), temp_dir());
+    generate(arg.path(), &["--tau", &tau(7)]);
+    let output = run_with_env(
+        env.path(),
+        &[("GENPARAMS_TAU", &tau(7))],
+        &[&SMALL[..], &["--tau-env", "GENPARAMS_TAU"]].concat(),
+    );
+    assert!(output.status.success());
+    assert_eq!(files(arg.path()), files(env.path()));
+    let error = generate_err(env.path(), &["--tau-env", "GENPARAMS_UNSET_TAU"]);
+    assert!(error.contains("GENPARAMS_UNSET_TAU"), "{}", error);
+}
+
+#[test]
+fn tau_sources_are_mutually_exclusive() {
+    let dir = temp_dir();
+    let error = generate_err(dir.path(), &["--seed-hex", "01", "--tau", &tau(7)]);
+    assert!(error.contains("cannot be used with"));
+}
@@ -0,0 +1,23 @@
+mod common;
+
+use common::{generate, run_err, run_ok, tau, temp_dir};
+use generate_params::bundle::Bundle;
+
+#[test]
+fn test_bundles_verify_and_tampered_proofs_are_rejected() {
+    let dir = temp_dir();
+    generate(dir.path(), &["--tau", &tau(7)]);
+    run_ok(
+        dir.path(),
+        &["make-test-bundle", "--num", "3", "--degree", "5"],
+    );
+    run_ok(dir.path(), &["verify-bundle"]);
+
+    let path = dir.path().join("params.bundle.json");
+    let path = path.to_str().unwrap();
+    let mut bundle = Bundle::load(path).unwrap();
+    bundle.openings[0].proof = bundle.openings[1].proof.clone();
+    bundle.save(path).unwrap();
+    let error = run_err(dir.path(), &["verify-bundle"]);
+    assert!(error.contains("1 of 3 openings are invalid"), "{}", error);
+}
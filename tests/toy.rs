@@ -0,0 +1,53 @@
+//! The full pipeline on the toy backend, at sizes that would take minutes on the real curves.
+
+// The pipeline includes the `--crash-after-chunk` hook, which only debug builds have.
+#![cfg(all(feature = "toy", debug_assertions))]
+
+mod common;
+
+use blstrs::Scalar;
+use common::{load_manifest, run_err, run_ok, tau, temp_dir};
+use ff::Field;
+use generate_params::reader;
+use generate_params::toy::{ToyG1, ToyG2};
+use group::Group;
+use std::time::{Duration, Instant};
+
+#[test]
+fn large_toy_sets_go_through_the_whole_pipeline() {
+    let dir = temp_dir();
+    let args = [
+        "--g1-count",
+        "100000",
+        "--g2-count",
+        "20000",
+        "--g1-chunk-length",
+        "10000",
+        "--g2-chunk-length",
+        "10000",
+        "--tau",
+        &tau(7),
+    ];
+    let start = Instant::now();
+    run_err(
+        dir.path(),
+        &[&args[..], &["--crash-after-chunk", "3"]].concat(),
+    );
+    run_ok(dir.path(), &[&args[..], &["--resume"]].concat());
+    run_ok(
+        dir.path(),
+        &["validate", "--check-hashes", "--check-points"],
+    );
+    assert!(start.elapsed() < Duration::from_secs(60));
+
+    let manifest = load_manifest(dir.path(), "params.json");
+    let tau = Scalar::from(7u64);
+    let g1 = &manifest.curves["G1"].layout;
+    let g2 = &manifest.curves["G2"].layout;
+    for index in [0, 1, 39_999, 99_999] {
+        let expected = ToyG1::generator() * tau.pow_vartime([index]);
+        assert_eq!(reader::read_point::<ToyG1>(g1, index).unwrap(), expected);
+    }
+    let expected = ToyG2::generator() * tau.pow_vartime([19_999]);
+    assert_eq!(reader::read_point::<ToyG2>(g2, 19_999).unwrap(), expected);
+}
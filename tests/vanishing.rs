@@ -0,0 +1,18 @@
+mod common;
+
+use blstrs::Scalar;
+use common::{generate, run_ok, tau, temp_dir};
+use generate_params::curve::G1;
+use group::{Group, GroupEncoding};
+
+#[test]
+fn vanishing_commitment_is_tau_to_the_n_times_g_minus_g() {
+    let dir = temp_dir();
+    generate(dir.path(), &["--tau", &tau(7)]);
+    let output = run_ok(dir.path(), &["vanishing-commitment", "--n", "5"]);
+    let expected = G1::generator() * Scalar::from(7u64.pow(5)) - G1::generator();
+    assert_eq!(
+        String::from_utf8(output.stdout).unwrap().trim(),
+        format!("0x{}", hex::encode(expected.to_bytes()))
+    );
+}
@@ -0,0 +1,33 @@
+mod common;
+
+use common::{generate, run_err, run_ok, tau, temp_dir};
+use generate_params::curve::{Encoding, G1, G2, Point};
+
+#[test]
+fn trimmed_verifier_keys_check_openings_of_their_set() {
+    let dir = temp_dir();
+    generate(dir.path(), &["--tau", &tau(7)]);
+    run_ok(dir.path(), &["trim-verifier-key"]);
+    let key = std::fs::read(dir.path().join("params.vk")).unwrap();
+    // The magic bytes, then the G1 generator, the G2 generator, and tau·G2.
+    assert_eq!(
+        key.len(),
+        8 + G1::encoded_size(Encoding::Compressed) + 2 * G2::encoded_size(Encoding::Compressed)
+    );
+    run_ok(
+        dir.path(),
+        &["make-test-bundle", "--num", "2", "--degree", "5"],
+    );
+    run_ok(
+        dir.path(),
+        &["verify-bundle", "--verifier-key", "params.vk"],
+    );
+
+    let other = temp_dir();
+    generate(other.path(), &["--tau", &tau(8)]);
+    let other_key = other.path().join("params.vk");
+    let other_key = other_key.to_str().unwrap();
+    run_ok(other.path(), &["trim-verifier-key"]);
+    let error = run_err(dir.path(), &["verify-bundle", "--verifier-key", other_key]);
+    assert!(error.contains("2 of 2 openings are invalid"), "{}", error);
+}
@@ -0,0 +1,33 @@
+mod common;
+
+use common::{generate_err, tau, temp_dir};
+
+#[test]
+fn unwritable_directories_are_rejected_before_tau_is_read() {
+    let dir = temp_dir();
+    // Read first, the unset variable would be reported instead.
+    let error = generate_err(
+        dir.path(),
+        &[
+            "--tau-env",
+            "GENPARAMS_UNSET_TAU",
+            "--g2-pattern",
+            "missing/g2_{}.bin",
+        ],
+    );
+    assert!(error.contains("missing is not writable"), "{}", error);
+    assert!(std::fs::read_dir(dir.path()).unwrap().next().is_none());
+
+    let error = generate_err(
+        dir.path(),
+        &[
+            "--tau",
+            &tau(7),
+            "--tau-encrypt",
+            "missing/tau.json",
+            "--passphrase-env",
+            "GENPARAMS_UNSET_PASSPHRASE",
+        ],
+    );
+    assert!(error.contains("missing is not writable"), "{}", error);
+}
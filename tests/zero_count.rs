@@ -0,0 +1,44 @@
+mod common;
+
+use common::{files, run_ok, tau, temp_dir};
+use generate_params::manifest::Manifest;
+
+#[test]
+fn zero_counts_write_no_chunks_and_an_empty_manifest() {
+    let dir = temp_dir();
+    let output = run_ok(
+        dir.path(),
+        &["--tau", &tau(7), "--g1-count", "0", "--g2-count", "0"],
+    );
+    assert!(
+        String::from_utf8(output.stdout)
+            .unwrap()
+            .contains("0/0 G1 pts")
+    );
+    let names: Vec<_> = files(dir.path())
+        .into_iter()
+        .map(|(name, _)| name)
+        .collect();
+    assert_eq!(names, ["params.json"]);
+    let manifest = Manifest::load(dir.path().join("params.json").to_str().unwrap()).unwrap();
+    assert_eq!(manifest.curves.len(), 2);
+    for curve in manifest.curves.values() {
+        assert_eq!(curve.layout.count, 0);
+        assert!(curve.chunks.is_empty());
+    }
+    run_ok(dir.path(), &["validate"]);
+}
+
+#[test]
+fn one_zero_count_leaves_the_other_curve_alone() {
+    let dir = temp_dir();
+    run_ok(
+        dir.path(),
+        &["--tau", &tau(7), "--g1-count", "5", "--g2-count", "0"],
+    );
+    let names: Vec<_> = files(dir.path())
+        .into_iter()
+        .map(|(name, _)| name)
+        .collect();
+    assert_eq!(names, ["g1_0.bin", "params.json"]);
+}